@@ -0,0 +1,30 @@
+use gracex::prelude::*;
+
+fn main() {
+    println!("Testing Plot...");
+
+    // sin(x) sampled at a few phase shifts: one line and one set of points per shift
+    let xs: Vec<f64> = (0..40).map(|i| i as f64 * 0.25).collect();
+    let grid = GridSource::from_fn(&xs, &[0.0, 1.0, 2.0], |row, column| {
+        (xs[column] + row as f64).sin()
+    });
+
+    let plot = Plot::new()
+        .data(grid)
+        .mapping(aes().x("x").y("z"))
+        .geom_line(aes().group("y").color("y"))
+        .geom_point(aes().color("z"))
+        .title("sin(x + shift)")
+        .x_label("x")
+        .y_label("sin");
+
+    match plot.save("test_output.png") {
+        Ok(warnings) => {
+            for warning in warnings {
+                eprintln!("Warning: {}", warning);
+            }
+            println!("✓ Successfully rendered to test_output.png");
+        }
+        Err(e) => eprintln!("✗ Error rendering: {}", e),
+    }
+}
@@ -0,0 +1,107 @@
+// #[derive(DataSource)]: exposes the fields of a struct as named columns, so a slice of
+// records can be plotted without building a DataFrame first.
+//
+// Every field type must convert into `gracex::data_source_records::FieldValue`
+// (numbers, bool, String, chrono dates and Options of those). Field attributes:
+//   #[data_source(rename = "other_name")]  column name to use instead of the field name
+//   #[data_source(skip)]                   do not expose the field
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr, parse_macro_input};
+
+#[proc_macro_derive(DataSource, attributes(data_source))]
+pub fn derive_data_source(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "DataSource can only be derived for structs with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "DataSource can only be derived for structs",
+            ));
+        }
+    };
+
+    let mut column_names = Vec::new();
+    let mut field_arms = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named fields have identifiers");
+        let mut column_name = ident.to_string();
+        let mut skip = false;
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("data_source"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    column_name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename = \"...\"` or `skip`"))
+                }
+            })?;
+        }
+
+        if skip {
+            continue;
+        }
+        if column_names.contains(&column_name) {
+            return Err(syn::Error::new_spanned(
+                ident,
+                format!("duplicate column name '{}'", column_name),
+            ));
+        }
+
+        field_arms.push(quote! {
+            #column_name => ::std::option::Option::Some(
+                ::gracex::data_source_records::FieldValue::from(
+                    ::std::clone::Clone::clone(&self.#ident)
+                )
+            ),
+        });
+        column_names.push(column_name);
+    }
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::gracex::data_source_records::DataSourceRecord
+            for #name #type_generics #where_clause
+        {
+            fn column_names() -> ::std::vec::Vec<&'static str> {
+                ::std::vec![#(#column_names),*]
+            }
+
+            fn field(
+                &self,
+                name: &str,
+            ) -> ::std::option::Option<::gracex::data_source_records::FieldValue> {
+                match name {
+                    #(#field_arms)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,315 @@
+// Checks a layer's aesthetic mapping against its geom and data before anything is drawn:
+// required aesthetics are present, mapped columns exist and have a type the aesthetic can
+// show, and fixed values have the right kind. Aesthetics the geom doesn't use are only
+// warnings, since drawing without them is still well defined.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::aesthetics::{AesValue, Aesthetic, AestheticMapping, AestheticValue};
+use crate::data_expression::ColumnExpr;
+use crate::data_source_self::DataSourceSelf;
+use crate::data_sources::{ColumnType, DataError};
+
+#[derive(Debug)]
+pub enum AestheticError {
+    MissingAesthetic {
+        layer: String,
+        aesthetic: Aesthetic,
+    },
+    // a mapped column is missing or has the wrong type
+    Column {
+        layer: String,
+        aesthetic: Aesthetic,
+        source: DataError,
+    },
+    InvalidFixedValue {
+        layer: String,
+        aesthetic: Aesthetic,
+        expected: &'static str,
+        value: AestheticValue,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AestheticWarning {
+    // specified, but not drawn by this layer's geom
+    UnusedAesthetic { layer: String, aesthetic: Aesthetic },
+}
+
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub errors: Vec<AestheticError>,
+    pub warnings: Vec<AestheticWarning>,
+}
+
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    // the first error if there is one, otherwise the warnings
+    pub fn into_result(self) -> Result<Vec<AestheticWarning>, AestheticError> {
+        match self.errors.into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(self.warnings),
+        }
+    }
+}
+
+impl fmt::Display for AestheticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AestheticError::MissingAesthetic { layer, aesthetic } => write!(
+                f,
+                "Layer '{}' requires the '{}' aesthetic, but it is not mapped",
+                layer, aesthetic
+            ),
+            AestheticError::Column {
+                layer,
+                aesthetic,
+                source,
+            } => write!(
+                f,
+                "Layer '{}', aesthetic '{}': {}",
+                layer, aesthetic, source
+            ),
+            AestheticError::InvalidFixedValue {
+                layer,
+                aesthetic,
+                expected,
+                value,
+            } => write!(
+                f,
+                "Layer '{}', aesthetic '{}': expected {}, got {:?}",
+                layer, aesthetic, expected, value
+            ),
+        }
+    }
+}
+
+impl Error for AestheticError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AestheticError::Column { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AestheticWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AestheticWarning::UnusedAesthetic { layer, aesthetic } => {
+                write!(f, "Layer '{}' ignores the '{}' aesthetic", layer, aesthetic)
+            }
+        }
+    }
+}
+
+// column types an aesthetic can show
+fn accepted_column_types(aesthetic: Aesthetic) -> &'static [ColumnType] {
+    use ColumnType::*;
+    match aesthetic {
+        Aesthetic::Size | Aesthetic::Alpha | Aesthetic::Linewidth => &[Numeric],
+        Aesthetic::Shape | Aesthetic::Linetype => &[Categorical, Boolean],
+        Aesthetic::X
+        | Aesthetic::Y
+        | Aesthetic::Color
+        | Aesthetic::Fill
+        | Aesthetic::Label
+        | Aesthetic::Group => &[Numeric, Categorical, Temporal, Boolean],
+    }
+}
+
+// fixed values must already be what the aesthetic draws with; Err names what was expected
+fn fixed_value_fits(aesthetic: Aesthetic, value: &AestheticValue) -> Result<(), &'static str> {
+    match aesthetic {
+        Aesthetic::Size | Aesthetic::Alpha | Aesthetic::Linewidth | Aesthetic::X | Aesthetic::Y => {
+            match value {
+                AestheticValue::Numeric(_) => Ok(()),
+                _ => Err("a number"),
+            }
+        }
+        Aesthetic::Color | Aesthetic::Fill => match value {
+            AestheticValue::Color(_) | AestheticValue::Text(_) => Ok(()),
+            _ => Err("a colour"),
+        },
+        Aesthetic::Shape | Aesthetic::Linetype => match value {
+            AestheticValue::Text(_) => Ok(()),
+            _ => Err("a name"),
+        },
+        Aesthetic::Label | Aesthetic::Group => Ok(()),
+    }
+}
+
+pub fn validate_mapping<S: DataSourceSelf + ?Sized>(
+    layer: &str,
+    mapping: &AestheticMapping,
+    required: &[Aesthetic],
+    optional: &[Aesthetic],
+    source: &S,
+) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    for &aesthetic in required {
+        if !mapping.contains(aesthetic) {
+            report.errors.push(AestheticError::MissingAesthetic {
+                layer: layer.to_string(),
+                aesthetic,
+            });
+        }
+    }
+
+    let schema = source.schema();
+    let column_type = |name: &str| {
+        schema
+            .iter()
+            .find(|column| column.name == name)
+            .map(|column| column.column_type)
+    };
+
+    for aesthetic in mapping.aesthetics() {
+        if !required.contains(&aesthetic) && !optional.contains(&aesthetic) {
+            report.warnings.push(AestheticWarning::UnusedAesthetic {
+                layer: layer.to_string(),
+                aesthetic,
+            });
+            continue;
+        }
+
+        let column_error = |source: DataError| AestheticError::Column {
+            layer: layer.to_string(),
+            aesthetic,
+            source,
+        };
+        match mapping.get(aesthetic) {
+            Some(AesValue::Mapped(expr)) => {
+                // a plain column keeps its own type, anything computed is numeric
+                let accepted = match expr {
+                    ColumnExpr::Column(_) => accepted_column_types(aesthetic),
+                    // polars checks the types of its own expressions when they're run
+                    #[cfg(feature = "polars")]
+                    ColumnExpr::Polars(_) => &[
+                        ColumnType::Numeric,
+                        ColumnType::Categorical,
+                        ColumnType::Temporal,
+                        ColumnType::Boolean,
+                    ],
+                    _ => &[ColumnType::Numeric],
+                };
+                for name in expr.referenced_columns() {
+                    match column_type(name) {
+                        None => report.errors.push(column_error(DataError::column_not_found(
+                            name,
+                            source.column_names(),
+                        ))),
+                        Some(actual) if !accepted.contains(&actual) => {
+                            let expected: Vec<&str> =
+                                accepted.iter().map(ColumnType::name).collect();
+                            report.errors.push(column_error(DataError::type_mismatch(
+                                name,
+                                &expected.join(" or "),
+                                actual.name(),
+                            )))
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+            Some(AesValue::Fixed(value)) => {
+                if let Err(expected) = fixed_value_fits(aesthetic, value) {
+                    report.errors.push(AestheticError::InvalidFixedValue {
+                        layer: layer.to_string(),
+                        aesthetic,
+                        expected,
+                        value: value.clone(),
+                    });
+                }
+            }
+            None => {}
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aesthetics::aes;
+    use crate::data_sources::OwnedColumnSource;
+
+    const POINT_REQUIRED: &[Aesthetic] = &[Aesthetic::X, Aesthetic::Y];
+    const POINT_OPTIONAL: &[Aesthetic] = &[Aesthetic::Color, Aesthetic::Size];
+
+    fn weight() -> OwnedColumnSource {
+        OwnedColumnSource {
+            name: "weight".to_string(),
+            data: vec![60.0, 70.0],
+        }
+    }
+
+    #[test]
+    fn test_missing_aesthetics_and_columns_are_errors() {
+        let mapping = aes().x("weight").size("wieght");
+        let report = validate_mapping("point", &mapping, POINT_REQUIRED, POINT_OPTIONAL, &weight());
+
+        assert_eq!(report.errors.len(), 2);
+        assert_eq!(
+            report.errors[0].to_string(),
+            "Layer 'point' requires the 'y' aesthetic, but it is not mapped"
+        );
+        assert_eq!(
+            report.errors[1].to_string(),
+            "Layer 'point', aesthetic 'size': Column 'wieght' not found; \
+             did you mean 'weight'? (available columns: weight)"
+        );
+        assert!(report.errors[1].source().is_some());
+    }
+
+    #[test]
+    fn test_unused_aesthetics_are_warnings() {
+        let mapping = aes()
+            .x("weight")
+            .y(ColumnExpr::column("weight").ln())
+            .linetype("weight");
+        let warnings =
+            validate_mapping("point", &mapping, POINT_REQUIRED, POINT_OPTIONAL, &weight())
+                .into_result()
+                .unwrap();
+        assert_eq!(
+            warnings,
+            vec![AestheticWarning::UnusedAesthetic {
+                layer: "point".to_string(),
+                aesthetic: Aesthetic::Linetype,
+            }]
+        );
+
+        let fixed = aes().x("weight").y("weight").set(Aesthetic::Size, "large");
+        assert!(matches!(
+            validate_mapping("point", &fixed, POINT_REQUIRED, POINT_OPTIONAL, &weight())
+                .into_result(),
+            Err(AestheticError::InvalidFixedValue {
+                expected: "a number",
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_column_types_must_suit_the_aesthetic() {
+        use polars::prelude::df;
+
+        let df = df! { "x" => &[1.0, 2.0], "name" => &["a", "b"] }.unwrap();
+        let mapping = aes().x("x").y("x").size("name");
+        let err = validate_mapping("point", &mapping, POINT_REQUIRED, POINT_OPTIONAL, &df)
+            .into_result()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Layer 'point', aesthetic 'size': Column 'name' has type categorical, expected numeric"
+        );
+    }
+}
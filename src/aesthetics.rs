@@ -0,0 +1,540 @@
+// Aesthetic mappings: which visual property of a geom shows which data column.
+// A mapped aesthetic takes one value per row from the data (through a column expression),
+// a fixed aesthetic is the same value for every row, and aesthetics left unspecified fall
+// back to a default. Mapped values are still data values here; scales turn them into
+// visual values (pixels, colours) later.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::data_expression::ColumnExpr;
+use crate::data_groups::DiscreteValue;
+use crate::data_source_self::DataSourceSelf;
+use crate::data_sources::DataError;
+use crate::primitives::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Aesthetic {
+    // position
+    X,
+    Y,
+
+    // visual
+    Color,
+    Fill,
+    Size,
+    Alpha,
+    Shape,
+    Linetype,
+    Linewidth,
+
+    // text
+    Label,
+
+    // which rows belong together (e.g. one line per group)
+    Group,
+}
+
+impl Aesthetic {
+    pub const ALL: [Aesthetic; 11] = [
+        Aesthetic::X,
+        Aesthetic::Y,
+        Aesthetic::Color,
+        Aesthetic::Fill,
+        Aesthetic::Size,
+        Aesthetic::Alpha,
+        Aesthetic::Shape,
+        Aesthetic::Linetype,
+        Aesthetic::Linewidth,
+        Aesthetic::Label,
+        Aesthetic::Group,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aesthetic::X => "x",
+            Aesthetic::Y => "y",
+            Aesthetic::Color => "color",
+            Aesthetic::Fill => "fill",
+            Aesthetic::Size => "size",
+            Aesthetic::Alpha => "alpha",
+            Aesthetic::Shape => "shape",
+            Aesthetic::Linetype => "linetype",
+            Aesthetic::Linewidth => "linewidth",
+            Aesthetic::Label => "label",
+            Aesthetic::Group => "group",
+        }
+    }
+
+    // accepts the british spelling "colour" too
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "colour" => Some(Aesthetic::Color),
+            _ => Aesthetic::ALL
+                .into_iter()
+                .find(|aesthetic| aesthetic.name() == name),
+        }
+    }
+
+    // value used when a layer neither maps nor sets the aesthetic;
+    // position, label and group have no sensible default
+    pub fn default_value(&self) -> Option<AestheticValue> {
+        match self {
+            Aesthetic::Color => Some(AestheticValue::Color(Color::default())),
+            Aesthetic::Fill => Some(AestheticValue::Color(Color {
+                r: 89,
+                g: 89,
+                b: 89,
+                a: 255,
+            })),
+            Aesthetic::Size => Some(AestheticValue::Numeric(3.0)),
+            Aesthetic::Alpha => Some(AestheticValue::Numeric(1.0)),
+            Aesthetic::Shape => Some(AestheticValue::Text("circle".to_string())),
+            Aesthetic::Linetype => Some(AestheticValue::Text("solid".to_string())),
+            Aesthetic::Linewidth => Some(AestheticValue::Numeric(1.0)),
+            Aesthetic::X | Aesthetic::Y | Aesthetic::Label | Aesthetic::Group => None,
+        }
+    }
+}
+
+impl fmt::Display for Aesthetic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// a single value of an aesthetic: a fixed value, or one row of a mapped column
+#[derive(Debug, Clone, PartialEq)]
+pub enum AestheticValue {
+    Numeric(f64),
+    Color(Color),
+    Text(String),
+    Boolean(bool),
+    Missing,
+}
+
+impl AestheticValue {
+    pub fn as_numeric(&self) -> Option<f64> {
+        match self {
+            AestheticValue::Numeric(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_color(&self) -> Option<Color> {
+        match self {
+            AestheticValue::Color(color) => Some(*color),
+            _ => None,
+        }
+    }
+}
+
+impl From<f64> for AestheticValue {
+    // NaN is a missing value
+    fn from(value: f64) -> Self {
+        if value.is_nan() {
+            AestheticValue::Missing
+        } else {
+            AestheticValue::Numeric(value)
+        }
+    }
+}
+
+impl From<Color> for AestheticValue {
+    fn from(color: Color) -> Self {
+        AestheticValue::Color(color)
+    }
+}
+
+impl From<&str> for AestheticValue {
+    fn from(text: &str) -> Self {
+        AestheticValue::Text(text.to_string())
+    }
+}
+
+impl From<String> for AestheticValue {
+    fn from(text: String) -> Self {
+        AestheticValue::Text(text)
+    }
+}
+
+impl From<bool> for AestheticValue {
+    fn from(value: bool) -> Self {
+        AestheticValue::Boolean(value)
+    }
+}
+
+impl From<DiscreteValue> for AestheticValue {
+    fn from(value: DiscreteValue) -> Self {
+        match value {
+            DiscreteValue::Boolean(value) => AestheticValue::Boolean(value),
+            DiscreteValue::Number(value) => AestheticValue::Numeric(value),
+            DiscreteValue::Text(text) => AestheticValue::Text(text),
+            DiscreteValue::Missing => AestheticValue::Missing,
+        }
+    }
+}
+
+impl From<&AestheticValue> for DiscreteValue {
+    // colours become levels named by their hex code
+    fn from(value: &AestheticValue) -> Self {
+        match value {
+            AestheticValue::Numeric(value) => DiscreteValue::from(*value),
+            AestheticValue::Color(color) => {
+                DiscreteValue::Text(format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b))
+            }
+            AestheticValue::Text(text) => DiscreteValue::Text(text.clone()),
+            AestheticValue::Boolean(value) => DiscreteValue::Boolean(*value),
+            AestheticValue::Missing => DiscreteValue::Missing,
+        }
+    }
+}
+
+// mapped from the data, or fixed to one value for every row
+#[derive(Debug, Clone, PartialEq)]
+pub enum AesValue<T> {
+    Mapped(ColumnExpr),
+    Fixed(T),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AestheticMapping {
+    values: BTreeMap<Aesthetic, AesValue<AestheticValue>>,
+}
+
+// starts an empty mapping: aes().x("age").y("height").color("gender")
+pub fn aes() -> AestheticMapping {
+    AestheticMapping::default()
+}
+
+impl AestheticMapping {
+    // map a column (or column expression) to the aesthetic
+    pub fn map(mut self, aesthetic: Aesthetic, column: impl Into<ColumnExpr>) -> Self {
+        self.values
+            .insert(aesthetic, AesValue::Mapped(column.into()));
+        self
+    }
+
+    // set the aesthetic to the same value for every row
+    pub fn set(mut self, aesthetic: Aesthetic, value: impl Into<AestheticValue>) -> Self {
+        self.values.insert(aesthetic, AesValue::Fixed(value.into()));
+        self
+    }
+
+    pub fn x(self, column: impl Into<ColumnExpr>) -> Self {
+        self.map(Aesthetic::X, column)
+    }
+
+    pub fn y(self, column: impl Into<ColumnExpr>) -> Self {
+        self.map(Aesthetic::Y, column)
+    }
+
+    pub fn color(self, column: impl Into<ColumnExpr>) -> Self {
+        self.map(Aesthetic::Color, column)
+    }
+
+    pub fn fill(self, column: impl Into<ColumnExpr>) -> Self {
+        self.map(Aesthetic::Fill, column)
+    }
+
+    pub fn size(self, column: impl Into<ColumnExpr>) -> Self {
+        self.map(Aesthetic::Size, column)
+    }
+
+    pub fn alpha(self, column: impl Into<ColumnExpr>) -> Self {
+        self.map(Aesthetic::Alpha, column)
+    }
+
+    pub fn shape(self, column: impl Into<ColumnExpr>) -> Self {
+        self.map(Aesthetic::Shape, column)
+    }
+
+    pub fn linetype(self, column: impl Into<ColumnExpr>) -> Self {
+        self.map(Aesthetic::Linetype, column)
+    }
+
+    pub fn linewidth(self, column: impl Into<ColumnExpr>) -> Self {
+        self.map(Aesthetic::Linewidth, column)
+    }
+
+    pub fn label(self, column: impl Into<ColumnExpr>) -> Self {
+        self.map(Aesthetic::Label, column)
+    }
+
+    pub fn group(self, column: impl Into<ColumnExpr>) -> Self {
+        self.map(Aesthetic::Group, column)
+    }
+
+    pub fn get(&self, aesthetic: Aesthetic) -> Option<&AesValue<AestheticValue>> {
+        self.values.get(&aesthetic)
+    }
+
+    pub fn contains(&self, aesthetic: Aesthetic) -> bool {
+        self.values.contains_key(&aesthetic)
+    }
+
+    pub fn aesthetics(&self) -> impl Iterator<Item = Aesthetic> + '_ {
+        self.values.keys().copied()
+    }
+
+    // every column the mapped aesthetics read, each once
+    pub fn referenced_columns(&self) -> Vec<&str> {
+        let mut columns = Vec::new();
+        for value in self.values.values() {
+            if let AesValue::Mapped(expr) = value {
+                for name in expr.referenced_columns() {
+                    if !columns.contains(&name) {
+                        columns.push(name);
+                    }
+                }
+            }
+        }
+        columns
+    }
+
+    // this mapping, with anything it leaves unspecified taken from `parent`
+    // (a layer inherits from the plot-level mapping this way)
+    pub fn inherit(&self, parent: &AestheticMapping) -> AestheticMapping {
+        let mut values = parent.values.clone();
+        values.extend(self.values.clone());
+        AestheticMapping { values }
+    }
+
+    // one value per row for every mapped aesthetic; fixed values and defaults are
+    // kept as a single value rather than repeated
+    pub fn evaluate<S: DataSourceSelf + ?Sized>(
+        &self,
+        source: &S,
+    ) -> Result<EvaluatedAesthetics, DataError> {
+        let mut values = BTreeMap::new();
+        for (&aesthetic, value) in &self.values {
+            let evaluated = match value {
+                AesValue::Mapped(expr) => AestheticValues::Mapped(evaluate_mapped(expr, source)?),
+                AesValue::Fixed(value) => AestheticValues::Fixed(value.clone()),
+            };
+            values.insert(aesthetic, evaluated);
+        }
+        for aesthetic in Aesthetic::ALL {
+            if let (false, Some(default)) =
+                (values.contains_key(&aesthetic), aesthetic.default_value())
+            {
+                values.insert(aesthetic, AestheticValues::Fixed(default));
+            }
+        }
+        Ok(EvaluatedAesthetics {
+            n_rows: source.n_rows(),
+            values,
+        })
+    }
+}
+
+// plain column references are read as they are, so string and boolean columns can be
+// mapped too and temporal ones come out as seconds since the epoch; any other expression
+// is numeric
+fn evaluate_mapped<S: DataSourceSelf + ?Sized>(
+    expr: &ColumnExpr,
+    source: &S,
+) -> Result<Vec<AestheticValue>, DataError> {
+    if let ColumnExpr::Column(name) = expr {
+        return match source.get_numeric_column_filled(name) {
+            Ok(values) => Ok(values.into_iter().map(AestheticValue::from).collect()),
+            Err(DataError::TypeMismatch { .. }) => match source.get_temporal_column(name) {
+                Ok(column) => Ok(column
+                    .to_seconds()
+                    .into_iter()
+                    .map(AestheticValue::from)
+                    .collect()),
+                Err(DataError::TypeMismatch { .. }) => Ok(source
+                    .get_discrete_column(name)?
+                    .into_iter()
+                    .map(AestheticValue::from)
+                    .collect()),
+                Err(err) => Err(err),
+            },
+            Err(err) => Err(err),
+        };
+    }
+    Ok(expr
+        .evaluate(source)?
+        .into_iter()
+        .map(AestheticValue::from)
+        .collect())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AestheticValues {
+    Mapped(Vec<AestheticValue>),
+    Fixed(AestheticValue),
+}
+
+impl AestheticValues {
+    pub fn is_mapped(&self) -> bool {
+        matches!(self, AestheticValues::Mapped(_))
+    }
+
+    pub fn value(&self, row: usize) -> &AestheticValue {
+        match self {
+            AestheticValues::Mapped(values) => &values[row],
+            AestheticValues::Fixed(value) => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluatedAesthetics {
+    pub n_rows: usize,
+    values: BTreeMap<Aesthetic, AestheticValues>,
+}
+
+impl EvaluatedAesthetics {
+    pub fn get(&self, aesthetic: Aesthetic) -> Option<&AestheticValues> {
+        self.values.get(&aesthetic)
+    }
+
+    // the value for one row, None if the aesthetic is neither specified nor defaulted
+    pub fn value(&self, aesthetic: Aesthetic, row: usize) -> Option<&AestheticValue> {
+        self.get(aesthetic).map(|values| values.value(row))
+    }
+
+    // how many rows each mapped aesthetic is missing a value in, for those missing any
+    pub fn missing_counts(&self) -> Vec<(Aesthetic, usize)> {
+        self.values
+            .iter()
+            .filter_map(|(&aesthetic, values)| match values {
+                AestheticValues::Mapped(values) => {
+                    let count = values
+                        .iter()
+                        .filter(|value| **value == AestheticValue::Missing)
+                        .count();
+                    (count > 0).then_some((aesthetic, count))
+                }
+                AestheticValues::Fixed(_) => None,
+            })
+            .collect()
+    }
+
+    // true for every row where no mapped aesthetic is missing its value
+    pub fn complete_rows(&self) -> Vec<bool> {
+        let mut complete = vec![true; self.n_rows];
+        for values in self.values.values() {
+            if let AestheticValues::Mapped(values) = values {
+                for (complete, value) in complete.iter_mut().zip(values) {
+                    *complete &= *value != AestheticValue::Missing;
+                }
+            }
+        }
+        complete
+    }
+
+    // keeps the rows where `keep` is true, in every mapped aesthetic
+    pub fn retain_rows(&mut self, keep: &[bool]) {
+        for values in self.values.values_mut() {
+            if let AestheticValues::Mapped(values) = values {
+                let mut rows = keep.iter();
+                values.retain(|_| *rows.next().unwrap());
+            }
+        }
+        self.n_rows = keep.iter().filter(|&&keep_row| keep_row).count();
+    }
+
+    // per-row numbers, missing and non-numeric values as NaN
+    pub fn numeric(&self, aesthetic: Aesthetic) -> Option<Vec<f64>> {
+        let values = self.get(aesthetic)?;
+        Some(
+            (0..self.n_rows)
+                .map(|row| values.value(row).as_numeric().unwrap_or(f64::NAN))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_sources::OwnedColumnSource;
+
+    #[test]
+    fn test_mapped_fixed_and_default_aesthetics() {
+        let source = OwnedColumnSource {
+            name: "age".to_string(),
+            data: vec![20.0, f64::NAN, 40.0],
+        };
+        let red = Color {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
+
+        let mapping = aes()
+            .x("age")
+            .y(ColumnExpr::column("age") * 2.0)
+            .set(Aesthetic::Color, red);
+        let evaluated = mapping.evaluate(&source).unwrap();
+
+        assert_eq!(
+            evaluated.get(Aesthetic::X),
+            Some(&AestheticValues::Mapped(vec![
+                AestheticValue::Numeric(20.0),
+                AestheticValue::Missing,
+                AestheticValue::Numeric(40.0),
+            ]))
+        );
+        let y = evaluated.numeric(Aesthetic::Y).unwrap();
+        assert_eq!((y[0], y[2]), (40.0, 80.0));
+        assert!(y[1].is_nan());
+
+        // fixed values and defaults are the same for every row
+        assert_eq!(
+            evaluated.value(Aesthetic::Color, 2),
+            Some(&AestheticValue::Color(red))
+        );
+        assert_eq!(
+            evaluated.value(Aesthetic::Size, 1),
+            Some(&AestheticValue::Numeric(3.0))
+        );
+        assert_eq!(evaluated.get(Aesthetic::Label), None);
+
+        assert!(matches!(
+            aes().x("height").evaluate(&source),
+            Err(DataError::ColumnNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_layer_mapping_inherits_from_plot_mapping() {
+        let plot = aes().x("age").y("height").color("gender");
+        let layer = aes().set(Aesthetic::Color, "blue").size("weight");
+
+        let combined = layer.inherit(&plot);
+        assert_eq!(
+            combined.aesthetics().collect::<Vec<_>>(),
+            vec![
+                Aesthetic::X,
+                Aesthetic::Y,
+                Aesthetic::Color,
+                Aesthetic::Size
+            ]
+        );
+        assert_eq!(
+            combined.get(Aesthetic::Color),
+            Some(&AesValue::Fixed(AestheticValue::Text("blue".to_string())))
+        );
+        assert_eq!(Aesthetic::from_name("colour"), Some(Aesthetic::Color));
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_string_columns_map_to_text_values() {
+        use polars::prelude::df;
+
+        let df = df! { "gender" => &["f", "m"], "n" => &[1i32, 2] }.unwrap();
+        let evaluated = aes().color("gender").size("n").evaluate(&df).unwrap();
+
+        assert_eq!(
+            evaluated.value(Aesthetic::Color, 1),
+            Some(&AestheticValue::Text("m".to_string()))
+        );
+        assert_eq!(evaluated.numeric(Aesthetic::Size).unwrap(), vec![1.0, 2.0]);
+    }
+}
@@ -0,0 +1,88 @@
+// Coordinate systems: where a position inside the panel ends up on the canvas.
+// Positions arrive already scaled to 0..1 along each axis (y measured upwards),
+// so a coordinate system only has to place them within the panel rectangle.
+
+use crate::primitives::Point;
+
+// an area of the canvas in pixels, (x, y) being the top left corner
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+}
+
+pub trait CoordSystem {
+    fn transform(&self, x: f64, y: f64, panel: &Rect) -> Point;
+
+    // true when the x aesthetic runs vertically, so the axes swap sides
+    fn is_flipped(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CoordCartesian;
+
+impl CoordSystem for CoordCartesian {
+    fn transform(&self, x: f64, y: f64, panel: &Rect) -> Point {
+        Point {
+            x: panel.x + x * panel.width,
+            y: panel.y + (1.0 - y) * panel.height,
+        }
+    }
+}
+
+// x runs vertically and y horizontally, e.g. for horizontal bars
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CoordFlip;
+
+impl CoordSystem for CoordFlip {
+    fn transform(&self, x: f64, y: f64, panel: &Rect) -> Point {
+        CoordCartesian.transform(y, x, panel)
+    }
+
+    fn is_flipped(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cartesian_and_flipped_transforms() {
+        let panel = Rect {
+            x: 10.0,
+            y: 20.0,
+            width: 100.0,
+            height: 50.0,
+        };
+
+        // y is measured upwards from the bottom of the panel
+        assert_eq!(
+            CoordCartesian.transform(0.0, 0.0, &panel),
+            Point { x: 10.0, y: 70.0 }
+        );
+        assert_eq!(
+            CoordCartesian.transform(1.0, 1.0, &panel),
+            Point { x: 110.0, y: 20.0 }
+        );
+        assert_eq!(
+            CoordFlip.transform(1.0, 0.0, &panel),
+            Point { x: 10.0, y: 20.0 }
+        );
+    }
+}
@@ -0,0 +1,550 @@
+// Column expressions: computed columns evaluated against a data source, so `log(price)`
+// or `a / b` can be plotted without adding columns to the user's data.
+// Missing values (null / NaN) propagate through the arithmetic as NaN.
+
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+#[cfg(feature = "polars")]
+use polars::prelude::Expr;
+
+use crate::data_source_self::DataSourceSelf;
+use crate::data_sources::DataError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    // natural logarithm, `log` or `ln` when parsed
+    Ln,
+    Log10,
+    Log2,
+    Abs,
+    Sqrt,
+    Exp,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnExpr {
+    Column(String),
+    Constant(f64),
+    Binary(BinaryOp, Box<ColumnExpr>, Box<ColumnExpr>),
+    Negate(Box<ColumnExpr>),
+    Apply(Function, Box<ColumnExpr>),
+    // evaluated by polars; only sources backed by a DataFrame can evaluate it
+    #[cfg(feature = "polars")]
+    Polars(Expr),
+}
+
+impl BinaryOp {
+    fn apply(&self, left: f64, right: f64) -> f64 {
+        match self {
+            BinaryOp::Add => left + right,
+            BinaryOp::Sub => left - right,
+            BinaryOp::Mul => left * right,
+            BinaryOp::Div => left / right,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+        }
+    }
+}
+
+impl Function {
+    fn apply(&self, value: f64) -> f64 {
+        match self {
+            Function::Ln => value.ln(),
+            Function::Log10 => value.log10(),
+            Function::Log2 => value.log2(),
+            Function::Abs => value.abs(),
+            Function::Sqrt => value.sqrt(),
+            Function::Exp => value.exp(),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Function::Ln => "log",
+            Function::Log10 => "log10",
+            Function::Log2 => "log2",
+            Function::Abs => "abs",
+            Function::Sqrt => "sqrt",
+            Function::Exp => "exp",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "log" | "ln" => Some(Function::Ln),
+            "log10" => Some(Function::Log10),
+            "log2" => Some(Function::Log2),
+            "abs" => Some(Function::Abs),
+            "sqrt" => Some(Function::Sqrt),
+            "exp" => Some(Function::Exp),
+            _ => None,
+        }
+    }
+}
+
+impl ColumnExpr {
+    pub fn column(name: &str) -> Self {
+        ColumnExpr::Column(name.to_string())
+    }
+
+    pub fn constant(value: f64) -> Self {
+        ColumnExpr::Constant(value)
+    }
+
+    pub fn apply(self, function: Function) -> Self {
+        ColumnExpr::Apply(function, Box::new(self))
+    }
+
+    pub fn ln(self) -> Self {
+        self.apply(Function::Ln)
+    }
+
+    pub fn log10(self) -> Self {
+        self.apply(Function::Log10)
+    }
+
+    pub fn log2(self) -> Self {
+        self.apply(Function::Log2)
+    }
+
+    pub fn abs(self) -> Self {
+        self.apply(Function::Abs)
+    }
+
+    pub fn sqrt(self) -> Self {
+        self.apply(Function::Sqrt)
+    }
+
+    pub fn exp(self) -> Self {
+        self.apply(Function::Exp)
+    }
+
+    // names of all columns the expression reads; for polars expressions only the columns
+    // named with col(), not those picked by selectors such as all()
+    pub fn referenced_columns(&self) -> Vec<&str> {
+        let mut columns = Vec::new();
+        self.collect_columns(&mut columns);
+        columns
+    }
+
+    fn collect_columns<'a>(&'a self, columns: &mut Vec<&'a str>) {
+        match self {
+            ColumnExpr::Column(name) => {
+                if !columns.contains(&name.as_str()) {
+                    columns.push(name);
+                }
+            }
+            ColumnExpr::Constant(_) => {}
+            ColumnExpr::Binary(_, left, right) => {
+                left.collect_columns(columns);
+                right.collect_columns(columns);
+            }
+            ColumnExpr::Negate(inner) | ColumnExpr::Apply(_, inner) => {
+                inner.collect_columns(columns)
+            }
+            #[cfg(feature = "polars")]
+            ColumnExpr::Polars(expr) => {
+                for node in expr {
+                    if let Expr::Column(name) = node
+                        && !columns.contains(&name.as_str())
+                    {
+                        columns.push(name);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn evaluate<S: DataSourceSelf + ?Sized>(&self, source: &S) -> Result<Vec<f64>, DataError> {
+        match self {
+            ColumnExpr::Column(name) => source.get_numeric_column_filled(name),
+            ColumnExpr::Constant(value) => Ok(vec![*value; source.n_rows()]),
+            ColumnExpr::Binary(op, left, right) => {
+                let left = left.evaluate(source)?;
+                let right = right.evaluate(source)?;
+                Ok(left
+                    .iter()
+                    .zip(&right)
+                    .map(|(&left, &right)| op.apply(left, right))
+                    .collect())
+            }
+            ColumnExpr::Negate(inner) => {
+                Ok(inner.evaluate(source)?.iter().map(|value| -value).collect())
+            }
+            ColumnExpr::Apply(function, inner) => Ok(inner
+                .evaluate(source)?
+                .iter()
+                .map(|&value| function.apply(value))
+                .collect()),
+            #[cfg(feature = "polars")]
+            ColumnExpr::Polars(expr) => source.evaluate_polars_expr(expr),
+        }
+    }
+
+    // parses expressions such as `log(price)`, `a / b`, `-2 * abs(x - 1)`;
+    // column names that aren't plain identifiers go in backticks: `unit price` * 2
+    pub fn parse(input: &str) -> Result<Self, DataError> {
+        let mut parser = Parser {
+            input,
+            tokens: tokenize(input)?,
+            position: 0,
+        };
+        let expr = parser.expression()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(parser.error(&format!("unexpected {}", token))),
+        }
+    }
+}
+
+impl fmt::Display for ColumnExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnExpr::Column(name) if is_identifier(name) => write!(f, "{}", name),
+            ColumnExpr::Column(name) => write!(f, "`{}`", name),
+            ColumnExpr::Constant(value) => write!(f, "{}", value),
+            ColumnExpr::Binary(op, left, right) => {
+                write!(f, "({} {} {})", left, op.symbol(), right)
+            }
+            ColumnExpr::Negate(inner) => write!(f, "-{}", inner),
+            ColumnExpr::Apply(function, inner) => write!(f, "{}({})", function.name(), inner),
+            #[cfg(feature = "polars")]
+            ColumnExpr::Polars(expr) => write!(f, "{}", expr),
+        }
+    }
+}
+
+impl From<&str> for ColumnExpr {
+    fn from(name: &str) -> Self {
+        ColumnExpr::column(name)
+    }
+}
+
+impl From<f64> for ColumnExpr {
+    fn from(value: f64) -> Self {
+        ColumnExpr::constant(value)
+    }
+}
+
+#[cfg(feature = "polars")]
+impl From<Expr> for ColumnExpr {
+    fn from(expr: Expr) -> Self {
+        ColumnExpr::Polars(expr)
+    }
+}
+
+macro_rules! binary_operator {
+    ($trait:ident, $method:ident, $op:expr) => {
+        impl<R: Into<ColumnExpr>> $trait<R> for ColumnExpr {
+            type Output = ColumnExpr;
+
+            fn $method(self, rhs: R) -> ColumnExpr {
+                ColumnExpr::Binary($op, Box::new(self), Box::new(rhs.into()))
+            }
+        }
+    };
+}
+
+binary_operator!(Add, add, BinaryOp::Add);
+binary_operator!(Sub, sub, BinaryOp::Sub);
+binary_operator!(Mul, mul, BinaryOp::Mul);
+binary_operator!(Div, div, BinaryOp::Div);
+
+impl Neg for ColumnExpr {
+    type Output = ColumnExpr;
+
+    fn neg(self) -> ColumnExpr {
+        ColumnExpr::Negate(Box::new(self))
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+}
+
+// parsing
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Operator(char),
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "number {}", value),
+            Token::Name(name) => write!(f, "name '{}'", name),
+            Token::Operator(op) => write!(f, "'{}'", op),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, DataError> {
+    let invalid = |message: String| DataError::InvalidExpression {
+        expression: input.to_string(),
+        message,
+    };
+
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek() {
+                // exponent signs belong to the number: 1e-3
+                let exponent_sign = (c == '-' || c == '+') && number.ends_with(['e', 'E']);
+                if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign {
+                    number.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let value = number
+                .parse()
+                .map_err(|_| invalid(format!("invalid number '{}'", number)))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut name = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_alphanumeric() || c == '_' || c == '.' {
+                    name.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Name(name));
+        } else if c == '`' {
+            chars.next();
+            let mut name = String::new();
+            loop {
+                match chars.next() {
+                    Some('`') => break,
+                    Some(c) => name.push(c),
+                    None => return Err(invalid(format!("unterminated column name '`{}'", name))),
+                }
+            }
+            tokens.push(Token::Name(name));
+        } else if "+-*/".contains(c) {
+            chars.next();
+            tokens.push(Token::Operator(c));
+        } else if c == '(' {
+            chars.next();
+            tokens.push(Token::Open);
+        } else if c == ')' {
+            chars.next();
+            tokens.push(Token::Close);
+        } else {
+            return Err(invalid(format!("unexpected character '{}'", c)));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn error(&self, message: &str) -> DataError {
+        DataError::InvalidExpression {
+            expression: self.input.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    // expression := term (('+' | '-') term)*
+    fn expression(&mut self) -> Result<ColumnExpr, DataError> {
+        let mut expr = self.term()?;
+        while let Some(Token::Operator(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.next();
+            let right = self.term()?;
+            expr = if op == '+' {
+                expr + right
+            } else {
+                expr - right
+            };
+        }
+        Ok(expr)
+    }
+
+    // term := unary (('*' | '/') unary)*
+    fn term(&mut self) -> Result<ColumnExpr, DataError> {
+        let mut expr = self.unary()?;
+        while let Some(Token::Operator(op @ ('*' | '/'))) = self.peek().cloned() {
+            self.next();
+            let right = self.unary()?;
+            expr = if op == '*' {
+                expr * right
+            } else {
+                expr / right
+            };
+        }
+        Ok(expr)
+    }
+
+    // unary := '-' unary | primary
+    fn unary(&mut self) -> Result<ColumnExpr, DataError> {
+        if self.peek() == Some(&Token::Operator('-')) {
+            self.next();
+            return Ok(-self.unary()?);
+        }
+        self.primary()
+    }
+
+    // primary := number | name | function '(' expression ')' | '(' expression ')'
+    fn primary(&mut self) -> Result<ColumnExpr, DataError> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(ColumnExpr::constant(value)),
+            Some(Token::Name(name)) if self.peek() == Some(&Token::Open) => {
+                let function = Function::from_name(&name)
+                    .ok_or_else(|| self.error(&format!("unknown function '{}'", name)))?;
+                self.next();
+                let argument = self.expression()?;
+                self.expect_close()?;
+                Ok(argument.apply(function))
+            }
+            Some(Token::Name(name)) => Ok(ColumnExpr::Column(name)),
+            Some(Token::Open) => {
+                let expr = self.expression()?;
+                self.expect_close()?;
+                Ok(expr)
+            }
+            Some(token) => Err(self.error(&format!("unexpected {}", token))),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+
+    fn expect_close(&mut self) -> Result<(), DataError> {
+        match self.next() {
+            Some(Token::Close) => Ok(()),
+            _ => Err(self.error("missing ')'")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_sources::OwnedColumnSource;
+
+    fn price() -> OwnedColumnSource {
+        OwnedColumnSource {
+            name: "price".to_string(),
+            data: vec![1.0, 10.0, 100.0],
+        }
+    }
+
+    #[test]
+    fn test_evaluate_arithmetic_and_functions() {
+        let source = price();
+
+        let doubled = ColumnExpr::column("price") * 2.0 + 1.0;
+        assert_eq!(doubled.evaluate(&source).unwrap(), vec![3.0, 21.0, 201.0]);
+
+        let logged = ColumnExpr::column("price").log10();
+        assert_eq!(logged.evaluate(&source).unwrap(), vec![0.0, 1.0, 2.0]);
+
+        let ratio = ColumnExpr::column("price") / ColumnExpr::column("price");
+        assert_eq!(ratio.evaluate(&source).unwrap(), vec![1.0, 1.0, 1.0]);
+
+        assert!(matches!(
+            ColumnExpr::column("cost").evaluate(&source),
+            Err(DataError::ColumnNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_expressions() {
+        let parsed = ColumnExpr::parse("log(price)").unwrap();
+        assert_eq!(parsed, ColumnExpr::column("price").ln());
+
+        let parsed = ColumnExpr::parse("a / b - 2 * -c").unwrap();
+        let expected = ColumnExpr::column("a") / ColumnExpr::column("b")
+            - ColumnExpr::constant(2.0) * -ColumnExpr::column("c");
+        assert_eq!(parsed, expected);
+        assert_eq!(parsed.referenced_columns(), vec!["a", "b", "c"]);
+
+        let parsed = ColumnExpr::parse("abs(`unit price` - 1e-3)").unwrap();
+        assert_eq!(parsed.referenced_columns(), vec!["unit price"]);
+        assert_eq!(parsed.to_string(), "abs((`unit price` - 0.001))");
+
+        for invalid in ["log(price", "foo(x)", "a +", "a $ b", "`unit price + 1"] {
+            assert!(matches!(
+                ColumnExpr::parse(invalid),
+                Err(DataError::InvalidExpression { .. })
+            ));
+        }
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_polars_expression_passthrough() {
+        use polars::prelude::{col, df};
+
+        let df = df! { "a" => &[1.0, 2.0], "b" => &[3.0, 4.0] }.unwrap();
+        let expr = ColumnExpr::from(col("a") + col("b")) * 10.0;
+        assert_eq!(expr.evaluate(&df).unwrap(), vec![40.0, 60.0]);
+        assert_eq!(expr.referenced_columns(), vec!["a", "b"]);
+
+        // other sources can't run polars expressions
+        assert!(ColumnExpr::from(col("price")).evaluate(&price()).is_err());
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_expressions_on_integer_columns() {
+        use polars::prelude::df;
+
+        let df = df! { "n" => &[1i64, 2, 3], "m" => &[4i32, 5, 6] }.unwrap();
+        let expr = ColumnExpr::column("n") * 2.0 + ColumnExpr::column("m");
+        assert_eq!(expr.evaluate(&df).unwrap(), vec![6.0, 9.0, 12.0]);
+
+        let series = df.column("n").unwrap().as_materialized_series().clone();
+        assert_eq!(
+            ColumnExpr::column("n").sqrt().evaluate(&series).unwrap()[0],
+            1.0
+        );
+    }
+}
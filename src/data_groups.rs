@@ -0,0 +1,261 @@
+// Splitting a data source into groups by one or more discrete columns, for colour-by-group
+// lines and facets. Each group is a row view plus its key values, and groups always come
+// out in level order whatever order the source produced them in: sorted by key, except
+// that categorical and enum columns keep the order of their levels.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::data_source_self::DataSourceSelf;
+use crate::data_source_view::RowView;
+use crate::data_sources::DataError;
+
+// a value of a discrete column; levels sort as booleans, then numbers, then text,
+// with missing values last
+#[derive(Debug, Clone)]
+pub enum DiscreteValue {
+    Boolean(bool),
+    Number(f64),
+    Text(String),
+    Missing,
+}
+
+impl DiscreteValue {
+    fn rank(&self) -> u8 {
+        match self {
+            DiscreteValue::Boolean(_) => 0,
+            DiscreteValue::Number(_) => 1,
+            DiscreteValue::Text(_) => 2,
+            DiscreteValue::Missing => 3,
+        }
+    }
+}
+
+impl From<f64> for DiscreteValue {
+    // NaN is a missing value, not a level of its own
+    fn from(value: f64) -> Self {
+        if value.is_nan() {
+            DiscreteValue::Missing
+        } else {
+            DiscreteValue::Number(value)
+        }
+    }
+}
+
+impl From<&str> for DiscreteValue {
+    fn from(text: &str) -> Self {
+        DiscreteValue::Text(text.to_string())
+    }
+}
+
+impl From<String> for DiscreteValue {
+    fn from(text: String) -> Self {
+        DiscreteValue::Text(text)
+    }
+}
+
+impl From<bool> for DiscreteValue {
+    fn from(value: bool) -> Self {
+        DiscreteValue::Boolean(value)
+    }
+}
+
+impl Ord for DiscreteValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (DiscreteValue::Boolean(a), DiscreteValue::Boolean(b)) => a.cmp(b),
+            (DiscreteValue::Number(a), DiscreteValue::Number(b)) => a.total_cmp(b),
+            (DiscreteValue::Text(a), DiscreteValue::Text(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl PartialOrd for DiscreteValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for DiscreteValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for DiscreteValue {}
+
+impl fmt::Display for DiscreteValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscreteValue::Boolean(value) => write!(f, "{}", value),
+            DiscreteValue::Number(value) => write!(f, "{}", value),
+            DiscreteValue::Text(value) => write!(f, "{}", value),
+            DiscreteValue::Missing => write!(f, "NA"),
+        }
+    }
+}
+
+// key values of a group, and the rows holding them
+pub type GroupIndices = Vec<(Vec<DiscreteValue>, Vec<usize>)>;
+
+// row indices of each distinct key, keys in level order; sources with a native
+// group-by (DataFrame) override DataSourceSelf::group_indices instead of using this
+pub(crate) fn group_rows_by_value<S: DataSourceSelf + ?Sized>(
+    source: &S,
+    columns: &[&str],
+) -> Result<GroupIndices, DataError> {
+    let key_columns = columns
+        .iter()
+        .map(|&name| source.get_discrete_column(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(group_rows(&key_columns, source.n_rows()))
+}
+
+// row indices of each distinct key across the columns of values, keys in level order
+pub(crate) fn group_rows(key_columns: &[Vec<DiscreteValue>], n_rows: usize) -> GroupIndices {
+    let mut groups: BTreeMap<Vec<DiscreteValue>, Vec<usize>> = BTreeMap::new();
+    for row in 0..n_rows {
+        let key = key_columns
+            .iter()
+            .map(|column| column[row].clone())
+            .collect();
+        groups.entry(key).or_default().push(row);
+    }
+    groups.into_iter().collect()
+}
+
+pub struct Group<'a, S: DataSourceSelf + ?Sized> {
+    // one value per grouping column, in the order the columns were given
+    pub keys: Vec<DiscreteValue>,
+    pub view: RowView<'a, S>,
+}
+
+// splits the source into one group per distinct combination of the columns' values;
+// without columns the whole source is a single group
+pub fn group_by<'a, S: DataSourceSelf + ?Sized>(
+    source: &'a S,
+    columns: &[&str],
+) -> Result<Vec<Group<'a, S>>, DataError> {
+    if columns.is_empty() {
+        return Ok(vec![Group {
+            keys: Vec::new(),
+            view: RowView::all(source),
+        }]);
+    }
+
+    let level_codes = columns
+        .iter()
+        .map(|&name| source.get_level_codes(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut groups = source.group_indices(columns)?;
+    // a level's code stands in for its value, missing values still last
+    groups.sort_by_cached_key(|(keys, rows)| {
+        keys.iter()
+            .zip(&level_codes)
+            .map(|(key, codes)| match codes {
+                None => key.clone(),
+                Some(codes) => codes[rows[0]].map_or(DiscreteValue::Missing, |code| {
+                    DiscreteValue::Number(code as f64)
+                }),
+            })
+            .collect::<Vec<_>>()
+    });
+    groups
+        .into_iter()
+        .map(|(keys, rows)| {
+            Ok(Group {
+                keys,
+                view: RowView::new(source, rows)?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_sources::OwnedColumnSource;
+
+    #[test]
+    fn test_group_by_numeric_column_in_level_order() {
+        let source = OwnedColumnSource {
+            name: "cyl".to_string(),
+            data: vec![6.0, 4.0, f64::NAN, 6.0, 4.0],
+        };
+
+        let groups = group_by(&source, &["cyl"]).unwrap();
+        let keys: Vec<_> = groups.iter().map(|group| group.keys.clone()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                vec![DiscreteValue::Number(4.0)],
+                vec![DiscreteValue::Number(6.0)],
+                vec![DiscreteValue::Missing],
+            ]
+        );
+        assert_eq!(groups[0].view.rows(), &[1, 4]);
+        assert_eq!(
+            groups[1].view.get_numeric_column("cyl").unwrap(),
+            vec![6.0, 6.0]
+        );
+
+        assert_eq!(group_by(&source, &[]).unwrap()[0].view.n_rows(), 5);
+        assert!(group_by(&source, &["gear"]).is_err());
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_group_by_dataframe_columns() {
+        use polars::prelude::df;
+
+        let df = df! {
+            "species" => &[Some("b"), Some("a"), None, Some("b")],
+            "island" => &[1i32, 2, 1, 1],
+            "mass" => &[1.0, 2.0, 3.0, 4.0],
+        }
+        .unwrap();
+
+        let groups = group_by(&df, &["species", "island"]).unwrap();
+        let keys: Vec<_> = groups.iter().map(|group| group.keys.clone()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                vec![
+                    DiscreteValue::Text("a".to_string()),
+                    DiscreteValue::Number(2.0)
+                ],
+                vec![
+                    DiscreteValue::Text("b".to_string()),
+                    DiscreteValue::Number(1.0)
+                ],
+                vec![DiscreteValue::Missing, DiscreteValue::Number(1.0)],
+            ]
+        );
+        assert_eq!(groups[1].view.rows(), &[0, 3]);
+        assert_eq!(
+            groups[1].view.get_numeric_column("mass").unwrap(),
+            vec![1.0, 4.0]
+        );
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_group_by_enum_levels_in_their_order() {
+        use polars::prelude::{DataFrame, DataType, FrozenCategories, NamedFrom, Series};
+
+        let levels = FrozenCategories::new(["low", "medium", "high"]).unwrap();
+        let size = Series::new("size".into(), &["high", "low", "medium", "low"])
+            .cast(&DataType::from_frozen_categories(levels))
+            .unwrap();
+        let df = DataFrame::new(vec![size.into()]).unwrap();
+
+        let keys: Vec<DiscreteValue> = group_by(&df, &["size"])
+            .unwrap()
+            .into_iter()
+            .map(|group| group.keys[0].clone())
+            .collect();
+        assert_eq!(keys, vec!["low".into(), "medium".into(), "high".into()]);
+    }
+}
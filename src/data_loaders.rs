@@ -0,0 +1,143 @@
+// File loaders: read a file through polars straight into a DataFrame, which is a data
+// source as is. Column types are inferred by polars; CSV files also get dates parsed.
+
+use std::fs::File;
+use std::path::Path;
+
+use polars::frame::DataFrame;
+use polars::prelude::{CsvReadOptions, IpcReader, JsonLineReader, ParquetReader, SerReader};
+
+use crate::data_sources::DataError;
+
+// number of CSV rows polars looks at to decide column types
+const CSV_INFER_SCHEMA_ROWS: usize = 1000;
+
+pub fn read_csv(path: impl AsRef<Path>) -> Result<DataFrame, DataError> {
+    let path = path.as_ref();
+    // check the file up front: polars would otherwise report a bare io error
+    File::open(path).map_err(|err| DataError::io(path, err))?;
+
+    let df = CsvReadOptions::default()
+        .with_has_header(true)
+        .with_infer_schema_length(Some(CSV_INFER_SCHEMA_ROWS))
+        .map_parse_options(|options| options.with_try_parse_dates(true))
+        .try_into_reader_with_file_path(Some(path.to_path_buf()))?
+        .finish()?;
+    Ok(df)
+}
+
+pub fn read_parquet(path: impl AsRef<Path>) -> Result<DataFrame, DataError> {
+    let file = open(path.as_ref())?;
+    Ok(ParquetReader::new(file).finish()?)
+}
+
+// Arrow IPC, also known as Feather (v2)
+pub fn read_ipc(path: impl AsRef<Path>) -> Result<DataFrame, DataError> {
+    let file = open(path.as_ref())?;
+    Ok(IpcReader::new(file).finish()?)
+}
+
+// newline delimited JSON, one record per line
+pub fn read_ndjson(path: impl AsRef<Path>) -> Result<DataFrame, DataError> {
+    let file = open(path.as_ref())?;
+    Ok(JsonLineReader::new(file).finish()?)
+}
+
+// picks the loader from the file extension
+pub fn read_file(path: impl AsRef<Path>) -> Result<DataFrame, DataError> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+
+    match extension.as_deref() {
+        Some("csv") => read_csv(path),
+        Some("parquet") | Some("pq") => read_parquet(path),
+        Some("arrow") | Some("ipc") | Some("feather") => read_ipc(path),
+        Some("ndjson") | Some("jsonl") => read_ndjson(path),
+        _ => Err(DataError::UnsupportedFormat(path.display().to_string())),
+    }
+}
+
+fn open(path: &Path) -> Result<File, DataError> {
+    File::open(path).map_err(|err| DataError::io(path, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source_self::DataSourceSelf;
+    use crate::data_temporal::TemporalKind;
+    use polars::prelude::*;
+    use std::path::PathBuf;
+
+    fn temp_path(file_name: &str) -> PathBuf {
+        // the pid keeps concurrent test runs from clobbering each other's files
+        std::env::temp_dir().join(format!(
+            "gracex_loader_test_{}_{}",
+            std::process::id(),
+            file_name
+        ))
+    }
+
+    fn sample_frame() -> DataFrame {
+        df! {
+            "x" => &[1.0, 2.0, 3.0],
+            "group" => &["a", "b", "a"],
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn test_read_csv_infers_types() {
+        let path = temp_path("infer.csv");
+        std::fs::write(&path, "day,x,group\n2024-01-01,1.5,a\n2024-01-02,2.5,b\n").unwrap();
+
+        let df = read_file(&path).unwrap();
+        assert_eq!(df.get_numeric_column("x").unwrap(), &[1.5, 2.5]);
+        assert_eq!(
+            df.get_temporal_column("day").unwrap().kind,
+            TemporalKind::Date
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_round_trip_binary_and_json_formats() {
+        let mut df = sample_frame();
+
+        let parquet_path = temp_path("round_trip.parquet");
+        ParquetWriter::new(File::create(&parquet_path).unwrap())
+            .finish(&mut df)
+            .unwrap();
+        let ipc_path = temp_path("round_trip.feather");
+        IpcWriter::new(File::create(&ipc_path).unwrap())
+            .finish(&mut df)
+            .unwrap();
+        let ndjson_path = temp_path("round_trip.ndjson");
+        JsonWriter::new(File::create(&ndjson_path).unwrap())
+            .with_json_format(JsonFormat::JsonLines)
+            .finish(&mut df)
+            .unwrap();
+
+        for path in [parquet_path, ipc_path, ndjson_path] {
+            let loaded = read_file(&path).unwrap();
+            assert_eq!(loaded.get_numeric_column("x").unwrap(), &[1.0, 2.0, 3.0]);
+            assert!(loaded.has_columns("group"));
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_loader_errors() {
+        let missing = temp_path("does_not_exist.csv");
+        assert!(matches!(read_csv(&missing), Err(DataError::Io { .. })));
+
+        assert!(matches!(
+            read_file("data.xlsx"),
+            Err(DataError::UnsupportedFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+// Missing value handling: nulls (from polars) and NaN are both treated as missing.
+// Columns that are plotted together (x, y, size, ...) are extracted in one go so the
+// policy is applied to whole rows, and the columns stay aligned. A plot applies its
+// policy (Plot::missing_policy) to the aesthetics each layer maps the same way.
+
+use std::fmt;
+
+use crate::data_source_self::DataSourceSelf;
+use crate::data_sources::DataError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingPolicy {
+    // drop every row with a missing value in any requested column, and warn about it
+    // (the warning is returned, not printed)
+    #[default]
+    DropWithWarning,
+    // keep all rows, missing values come out as NaN
+    PropagateNan,
+    // refuse to extract when any requested column has a missing value
+    Error,
+}
+
+pub struct ExtractedColumns {
+    pub names: Vec<String>,
+    pub columns: Vec<Vec<f64>>,
+    pub dropped_rows: usize,
+}
+
+impl ExtractedColumns {
+    pub fn column(&self, name: &str) -> Option<&[f64]> {
+        self.names
+            .iter()
+            .position(|column_name| column_name == name)
+            .map(|index| self.columns[index].as_slice())
+    }
+
+    pub fn n_rows(&self) -> usize {
+        self.columns.first().map_or(0, |column| column.len())
+    }
+
+    // what DropWithWarning warns about, for the caller to report; None when nothing was dropped
+    pub fn warning(&self) -> Option<DroppedRowsWarning> {
+        (self.dropped_rows > 0).then(|| DroppedRowsWarning {
+            rows: self.dropped_rows,
+            columns: self.names.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DroppedRowsWarning {
+    pub rows: usize,
+    pub columns: Vec<String>,
+}
+
+impl fmt::Display for DroppedRowsWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Removed {} rows containing missing values in columns {:?}",
+            self.rows, self.columns
+        )
+    }
+}
+
+pub fn extract_numeric_columns<S: DataSourceSelf + ?Sized>(
+    source: &S,
+    names: &[&str],
+    policy: MissingPolicy,
+) -> Result<ExtractedColumns, DataError> {
+    let mut columns = Vec::with_capacity(names.len());
+    let mut keep = vec![true; source.n_rows()];
+
+    for &name in names {
+        let column = source.get_numeric_column_filled(name)?;
+        if column.len() != keep.len() {
+            return Err(DataError::LengthMismatch {
+                column: name.to_string(),
+                expected: keep.len(),
+                actual: column.len(),
+            });
+        }
+
+        let validity = source.validity_mask(name)?;
+        let n_missing = validity.iter().filter(|&&valid| !valid).count();
+        if n_missing > 0 && policy == MissingPolicy::Error {
+            return Err(DataError::MissingValues {
+                column: name.to_string(),
+                count: n_missing,
+            });
+        }
+
+        for (keep_row, valid) in keep.iter_mut().zip(validity) {
+            *keep_row &= valid;
+        }
+        columns.push(column);
+    }
+
+    let mut dropped_rows = 0;
+    if policy == MissingPolicy::DropWithWarning {
+        dropped_rows = keep.iter().filter(|&&keep_row| !keep_row).count();
+        if dropped_rows > 0 {
+            for column in columns.iter_mut() {
+                let mut rows = keep.iter();
+                column.retain(|_| *rows.next().unwrap());
+            }
+        }
+    }
+
+    Ok(ExtractedColumns {
+        names: names.iter().map(|name| name.to_string()).collect(),
+        columns,
+        dropped_rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_sources::OwnedColumnSource;
+    #[cfg(feature = "polars")]
+    use polars::prelude::*;
+
+    #[cfg(feature = "polars")]
+    fn data_frame_with_gaps() -> DataFrame {
+        df! {
+            "x" => &[Some(1.0), Some(2.0), None, Some(4.0)],
+            "y" => &[Some(10.0), Some(f64::NAN), Some(30.0), Some(40.0)],
+        }
+        .unwrap()
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_drop_with_warning_removes_incomplete_rows() {
+        let df = data_frame_with_gaps();
+        let extracted =
+            extract_numeric_columns(&df, &["x", "y"], MissingPolicy::DropWithWarning).unwrap();
+
+        assert_eq!(extracted.dropped_rows, 2);
+        assert_eq!(
+            extracted.warning().unwrap().to_string(),
+            "Removed 2 rows containing missing values in columns [\"x\", \"y\"]"
+        );
+        assert_eq!(extracted.n_rows(), 2);
+        assert_eq!(extracted.column("x").unwrap(), &[1.0, 4.0]);
+        assert_eq!(extracted.column("y").unwrap(), &[10.0, 40.0]);
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_propagate_nan_keeps_all_rows() {
+        let df = data_frame_with_gaps();
+        let extracted =
+            extract_numeric_columns(&df, &["x", "y"], MissingPolicy::PropagateNan).unwrap();
+
+        assert_eq!(extracted.dropped_rows, 0);
+        assert_eq!(extracted.warning(), None);
+        let x = extracted.column("x").unwrap();
+        assert_eq!(x.len(), 4);
+        assert!(x[2].is_nan());
+        assert!(extracted.column("y").unwrap()[1].is_nan());
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_error_policy_rejects_missing_values() {
+        let df = data_frame_with_gaps();
+        let result = extract_numeric_columns(&df, &["x"], MissingPolicy::Error);
+        assert!(matches!(
+            result,
+            Err(DataError::MissingValues { count: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_error_policy_accepts_complete_columns() {
+        let source = OwnedColumnSource {
+            name: "x".to_string(),
+            data: vec![1.0, 2.0],
+        };
+        let extracted = extract_numeric_columns(&source, &["x"], MissingPolicy::Error).unwrap();
+        assert_eq!(extracted.column("x").unwrap(), &[1.0, 2.0]);
+        assert_eq!(extracted.dropped_rows, 0);
+    }
+}
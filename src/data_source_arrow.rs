@@ -0,0 +1,376 @@
+// arrow-rs RecordBatch as a data source, for teams on DataFusion / arrow-rs without polars.
+// Float64 columns are lent straight out of the arrow buffers (zero copy), other numeric
+// columns (integers, f32, decimals) are cast to f64 when read filled.
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Date32Type, Date64Type, DurationMicrosecondType, DurationMillisecondType,
+    DurationNanosecondType, DurationSecondType, Float64Type, TimestampMicrosecondType,
+    TimestampMillisecondType, TimestampNanosecondType, TimestampSecondType,
+};
+use arrow_array::{Array, ArrayRef, ArrowPrimitiveType, RecordBatch};
+use arrow_cast::cast;
+use arrow_schema::{DataType, TimeUnit as ArrowTimeUnit};
+
+use crate::data_groups::DiscreteValue;
+use crate::data_source_self::DataSourceSelf;
+use crate::data_sources::{ColumnSchema, ColumnType, DataError};
+use crate::data_temporal::{
+    SECONDS_PER_DAY, TEMPORAL_TYPES, TemporalColumn, TemporalKind, TimeUnit,
+};
+
+impl DataSourceSelf for RecordBatch {
+    fn get_numeric_column(&self, name: &str) -> Result<&[f64], DataError> {
+        let column = batch_column(self, name)?;
+        if column.data_type() != &DataType::Float64 {
+            return Err(DataError::type_mismatch(name, "f64", column.data_type()));
+        }
+        // null slots hold arbitrary values in the buffer, so they can't be lent out as is
+        if column.null_count() > 0 {
+            return Err(DataError::MissingValues {
+                column: name.to_string(),
+                count: column.null_count(),
+            });
+        }
+        Ok(column.as_primitive::<Float64Type>().values())
+    }
+
+    fn n_rows(&self) -> usize {
+        self.num_rows()
+    }
+
+    fn has_columns(&self, name: &str) -> bool {
+        self.schema().column_with_name(name).is_some()
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        self.schema_ref()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect()
+    }
+
+    fn schema(&self) -> Vec<ColumnSchema> {
+        self.schema_ref()
+            .fields()
+            .iter()
+            .map(|field| ColumnSchema::new(field.name(), column_type(field.data_type())))
+            .collect()
+    }
+
+    fn get_numeric_column_filled(&self, name: &str) -> Result<Vec<f64>, DataError> {
+        float_values(batch_column(self, name)?, name)
+    }
+
+    fn get_temporal_column(&self, name: &str) -> Result<TemporalColumn, DataError> {
+        let column = batch_column(self, name)?;
+        let (kind, values) = match column.data_type() {
+            DataType::Date32 => (TemporalKind::Date, ticks::<Date32Type>(column, 1)),
+            // Date64 counts milliseconds, but always lands on whole days
+            DataType::Date64 => (
+                TemporalKind::Date,
+                ticks::<Date64Type>(column, 1)
+                    .into_iter()
+                    .map(|value| value.map(|millis| millis / (SECONDS_PER_DAY * 1_000)))
+                    .collect(),
+            ),
+            DataType::Timestamp(unit, time_zone) => {
+                let values = match unit {
+                    ArrowTimeUnit::Second => ticks::<TimestampSecondType>(column, 1_000),
+                    ArrowTimeUnit::Millisecond => ticks::<TimestampMillisecondType>(column, 1),
+                    ArrowTimeUnit::Microsecond => ticks::<TimestampMicrosecondType>(column, 1),
+                    ArrowTimeUnit::Nanosecond => ticks::<TimestampNanosecondType>(column, 1),
+                };
+                let kind = TemporalKind::Datetime {
+                    unit: time_unit_from_arrow(unit),
+                    time_zone: time_zone.as_ref().map(|zone| zone.to_string()),
+                };
+                (kind, values)
+            }
+            DataType::Duration(unit) => {
+                let values = match unit {
+                    ArrowTimeUnit::Second => ticks::<DurationSecondType>(column, 1_000),
+                    ArrowTimeUnit::Millisecond => ticks::<DurationMillisecondType>(column, 1),
+                    ArrowTimeUnit::Microsecond => ticks::<DurationMicrosecondType>(column, 1),
+                    ArrowTimeUnit::Nanosecond => ticks::<DurationNanosecondType>(column, 1),
+                };
+                let kind = TemporalKind::Duration {
+                    unit: time_unit_from_arrow(unit),
+                };
+                (kind, values)
+            }
+            other => return Err(DataError::type_mismatch(name, TEMPORAL_TYPES, other)),
+        };
+        Ok(TemporalColumn { kind, values })
+    }
+
+    fn get_discrete_column(&self, name: &str) -> Result<Vec<DiscreteValue>, DataError> {
+        discrete_values(batch_column(self, name)?, name)
+    }
+}
+
+// strings, dictionary-encoded values, booleans and numbers as grouping values
+fn discrete_values(column: &ArrayRef, name: &str) -> Result<Vec<DiscreteValue>, DataError> {
+    let text = |value: Option<&str>| value.map_or(DiscreteValue::Missing, DiscreteValue::from);
+    let values = match column.data_type() {
+        DataType::Utf8 => column.as_string::<i32>().iter().map(text).collect(),
+        DataType::LargeUtf8 => column.as_string::<i64>().iter().map(text).collect(),
+        DataType::Utf8View => column.as_string_view().iter().map(text).collect(),
+        DataType::Boolean => column
+            .as_boolean()
+            .iter()
+            .map(|value| value.map_or(DiscreteValue::Missing, DiscreteValue::from))
+            .collect(),
+        DataType::Dictionary(..) => {
+            let dictionary = column.as_any_dictionary();
+            let levels = discrete_values(dictionary.values(), name)?;
+            let keys = dictionary.normalized_keys();
+            (0..column.len())
+                .map(|row| match column.is_null(row) {
+                    true => DiscreteValue::Missing,
+                    false => levels[keys[row]].clone(),
+                })
+                .collect()
+        }
+        data_type if data_type.is_numeric() => float_values(column, name)?
+            .into_iter()
+            .map(DiscreteValue::from)
+            .collect(),
+        other => {
+            return Err(DataError::type_mismatch(
+                name,
+                "string, dictionary, boolean or numeric",
+                other,
+            ));
+        }
+    };
+    Ok(values)
+}
+
+// any numeric array as f64s, nulls filled with NaN
+fn float_values(column: &ArrayRef, name: &str) -> Result<Vec<f64>, DataError> {
+    let fill = |array: &ArrayRef| {
+        array
+            .as_primitive::<Float64Type>()
+            .iter()
+            .map(|value| value.unwrap_or(f64::NAN))
+            .collect()
+    };
+    match column.data_type() {
+        DataType::Float64 => Ok(fill(column)),
+        data_type if data_type.is_numeric() => cast(column, &DataType::Float64)
+            .map(|array| fill(&array))
+            .map_err(|_| DataError::type_mismatch(name, "numeric", data_type)),
+        other => Err(DataError::type_mismatch(name, "numeric", other)),
+    }
+}
+
+fn batch_column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a ArrayRef, DataError> {
+    batch
+        .column_by_name(name)
+        .ok_or_else(|| DataError::column_not_found(name, DataSourceSelf::column_names(batch)))
+}
+
+fn column_type(data_type: &DataType) -> ColumnType {
+    if data_type.is_numeric() {
+        ColumnType::Numeric
+    } else if data_type.is_temporal() {
+        ColumnType::Temporal
+    } else if data_type == &DataType::Boolean {
+        ColumnType::Boolean
+    } else {
+        ColumnType::Categorical
+    }
+}
+
+// raw integer values of a temporal array, scaled by `factor` to reach a supported unit
+fn ticks<T>(column: &ArrayRef, factor: i64) -> Vec<Option<i64>>
+where
+    T: ArrowPrimitiveType,
+    T::Native: Into<i64>,
+{
+    column
+        .as_primitive::<T>()
+        .iter()
+        .map(|value| value.map(|ticks| ticks.into() * factor))
+        .collect()
+}
+
+// there is no seconds unit on our side, seconds are stored as milliseconds
+fn time_unit_from_arrow(unit: &ArrowTimeUnit) -> TimeUnit {
+    match unit {
+        ArrowTimeUnit::Second | ArrowTimeUnit::Millisecond => TimeUnit::Milliseconds,
+        ArrowTimeUnit::Microsecond => TimeUnit::Microseconds,
+        ArrowTimeUnit::Nanosecond => TimeUnit::Nanoseconds,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use arrow_array::types::Int32Type;
+    use arrow_array::{
+        BooleanArray, Date32Array, DictionaryArray, Float32Array, Float64Array, Int64Array,
+        StringArray, TimestampSecondArray,
+    };
+    use std::sync::Arc;
+
+    fn sample_batch() -> RecordBatch {
+        RecordBatch::try_from_iter(vec![
+            (
+                "x",
+                Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0])) as ArrayRef,
+            ),
+            (
+                "y",
+                Arc::new(Float64Array::from(vec![Some(1.0), None, Some(3.0)])) as ArrayRef,
+            ),
+            (
+                "label",
+                Arc::new(StringArray::from(vec!["a", "b", "c"])) as ArrayRef,
+            ),
+            (
+                "day",
+                Arc::new(Date32Array::from(vec![0, 1, 2])) as ArrayRef,
+            ),
+            (
+                "stamp",
+                Arc::new(TimestampSecondArray::from(vec![1, 2, 3]).with_timezone("UTC"))
+                    as ArrayRef,
+            ),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_data_source_self_for_record_batch() {
+        let batch = sample_batch();
+
+        assert_eq!(batch.get_numeric_column("x").unwrap(), &[1.0, 2.0, 3.0]);
+        assert_eq!(batch.n_rows(), 3);
+        assert!(batch.has_columns("label"));
+        assert!(!batch.has_columns("z"));
+
+        // nulls can't be borrowed, but can be read filled
+        assert!(matches!(
+            batch.get_numeric_column("y"),
+            Err(DataError::MissingValues { count: 1, .. })
+        ));
+        assert_eq!(batch.validity_mask("y").unwrap(), vec![true, false, true]);
+
+        assert!(matches!(
+            batch.get_numeric_column("label"),
+            Err(DataError::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            batch.get_numeric_column("xx"),
+            Err(DataError::ColumnNotFound { .. })
+        ));
+
+        let column_types: Vec<_> = DataSourceSelf::schema(&batch)
+            .into_iter()
+            .map(|column| column.column_type)
+            .collect();
+        assert_eq!(
+            column_types,
+            vec![
+                ColumnType::Numeric,
+                ColumnType::Numeric,
+                ColumnType::Categorical,
+                ColumnType::Temporal,
+                ColumnType::Temporal,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_batch_temporal_columns() {
+        let batch = sample_batch();
+
+        let day = batch.get_temporal_column("day").unwrap();
+        assert_eq!(day.kind, TemporalKind::Date);
+        assert_eq!(day.values, vec![Some(0), Some(1), Some(2)]);
+
+        let stamp = batch.get_temporal_column("stamp").unwrap();
+        assert_eq!(
+            stamp.kind,
+            TemporalKind::Datetime {
+                unit: TimeUnit::Milliseconds,
+                time_zone: Some("UTC".to_string()),
+            }
+        );
+        assert_eq!(stamp.to_seconds(), vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_record_batch_discrete_columns() {
+        let level: DictionaryArray<Int32Type> =
+            vec![Some("lo"), None, Some("hi")].into_iter().collect();
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "label",
+                Arc::new(StringArray::from(vec![Some("a"), None, Some("a")])) as ArrayRef,
+            ),
+            ("level", Arc::new(level) as ArrayRef),
+            (
+                "flag",
+                Arc::new(BooleanArray::from(vec![true, false, true])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            batch.get_discrete_column("label").unwrap(),
+            vec!["a".into(), DiscreteValue::Missing, "a".into()]
+        );
+        assert_eq!(
+            batch.get_discrete_column("level").unwrap(),
+            vec!["lo".into(), DiscreteValue::Missing, "hi".into()]
+        );
+        assert_eq!(
+            batch.group_indices(&["flag"]).unwrap(),
+            vec![
+                (vec![false.into()], vec![1]),
+                (vec![true.into()], vec![0, 2])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_batch_integer_columns_are_plotted() {
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "n",
+                Arc::new(Int64Array::from(vec![Some(1), None, Some(3)])) as ArrayRef,
+            ),
+            (
+                "y",
+                Arc::new(Float32Array::from(vec![0.5, 1.5, 2.5])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        // only f64 is lent out, everything numeric can be read filled
+        assert!(matches!(
+            batch.get_numeric_column("n"),
+            Err(DataError::TypeMismatch { .. })
+        ));
+        let n = batch.get_numeric_column_filled("n").unwrap();
+        assert_eq!(n[0], 1.0);
+        assert!(n[1].is_nan());
+        assert_eq!(n[2], 3.0);
+        assert_eq!(
+            batch.get_numeric_column_filled("y").unwrap(),
+            vec![0.5, 1.5, 2.5]
+        );
+        assert_eq!(
+            batch.get_discrete_column("n").unwrap()[2],
+            DiscreteValue::from(3.0)
+        );
+
+        let plot = Plot::new().data(&batch).geom_point(aes().x("n").y("y"));
+        assert!(plot.build().is_ok());
+    }
+}
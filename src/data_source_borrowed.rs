@@ -1,5 +1,5 @@
 // lifetime pinned to the existence of the original data
-use crate::data_sources::{BorrowedColumnSource, DataError};
+use crate::data_sources::{BorrowedColumnSource, ColumnSchema, DataError};
 
 pub trait DataSourceBorrowed<'a> {
     fn get_numeric_column(&self, name: &str) -> Result<&'a [f64], DataError>;
@@ -7,17 +7,21 @@ pub trait DataSourceBorrowed<'a> {
     fn n_rows(&self) -> usize;
 
     fn has_columns(&self, name: &str) -> bool;
+
+    fn column_names(&self) -> Vec<String>;
+
+    // column names with their logical types; every column is numeric unless overridden
+    fn schema(&self) -> Vec<ColumnSchema> {
+        ColumnSchema::all_numeric(self.column_names())
+    }
 }
 
 impl<'a> DataSourceBorrowed<'a> for BorrowedColumnSource<'a> {
     fn get_numeric_column(&self, name: &str) -> Result<&'a [f64], DataError> {
         if name == self.name {
-            return Ok(&self.data);
+            Ok(self.data)
         } else {
-            return Err(DataError::ColumnNotFound(format!(
-                "Column '{}' not found",
-                name
-            )));
+            Err(DataError::column_not_found(name, [self.name.as_str()]))
         }
     }
 
@@ -28,8 +32,13 @@ impl<'a> DataSourceBorrowed<'a> for BorrowedColumnSource<'a> {
     fn has_columns(&self, name: &str) -> bool {
         self.name == name
     }
+
+    fn column_names(&self) -> Vec<String> {
+        vec![self.name.clone()]
+    }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*; // Imports everything from the outer file
 
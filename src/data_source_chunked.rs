@@ -0,0 +1,419 @@
+// Chunked data sources, for datasets too large to hold in memory at once.
+// Instead of lending a whole column, a chunked source yields the requested columns a few
+// rows at a time, and stats accumulate over the chunks (RunningExtent, RunningHistogram)
+// so memory stays bounded by the chunk size. Iterating again starts from the first row,
+// so two-pass stats (train the range, then bin) work too.
+
+use std::borrow::Cow;
+#[cfg(feature = "polars")]
+use std::num::NonZeroUsize;
+#[cfg(feature = "polars")]
+use std::sync::mpsc;
+
+#[cfg(feature = "polars")]
+use polars::frame::DataFrame;
+#[cfg(feature = "polars")]
+use polars::prelude::{Engine, PlanCallback};
+
+#[cfg(feature = "polars")]
+use crate::data_source_lazy::LazyFrameSource;
+use crate::data_source_self::DataSourceSelf;
+use crate::data_sources::DataError;
+
+pub const DEFAULT_CHUNK_SIZE: usize = 65_536;
+
+// the requested columns for a run of consecutive rows, missing values as NaN
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnChunk {
+    pub names: Vec<String>,
+    pub columns: Vec<Vec<f64>>,
+}
+
+impl ColumnChunk {
+    pub fn column(&self, name: &str) -> Option<&[f64]> {
+        self.names
+            .iter()
+            .position(|column_name| column_name == name)
+            .map(|index| self.columns[index].as_slice())
+    }
+
+    pub fn n_rows(&self) -> usize {
+        self.columns.first().map_or(0, |column| column.len())
+    }
+
+    #[cfg(feature = "polars")]
+    fn from_source<S: DataSourceSelf + ?Sized>(
+        source: &S,
+        names: &[String],
+    ) -> Result<Self, DataError> {
+        let columns = names
+            .iter()
+            .map(|name| source.get_numeric_column_filled(name))
+            .collect::<Result<_, _>>()?;
+        Ok(ColumnChunk {
+            names: names.to_vec(),
+            columns,
+        })
+    }
+}
+
+pub type ChunkIter<'a> = Box<dyn Iterator<Item = Result<ColumnChunk, DataError>> + 'a>;
+
+pub trait DataSourceChunked {
+    // chunks of at most `chunk_size` rows holding the requested columns, in row order;
+    // unknown columns are reported before the first chunk is read
+    fn chunks<'a>(
+        &'a self,
+        columns: &[&str],
+        chunk_size: usize,
+    ) -> Result<ChunkIter<'a>, DataError>;
+
+    // fallible, since finding the columns of a lazy query can fail
+    fn has_columns(&self, name: &str) -> Result<bool, DataError>;
+
+    fn column_names(&self) -> Result<Vec<String>, DataError>;
+}
+
+// any in-memory source, so stats written against chunks also accept ordinary sources
+pub struct InMemoryChunks<'a, S: DataSourceSelf + ?Sized> {
+    source: &'a S,
+}
+
+impl<'a, S: DataSourceSelf + ?Sized> InMemoryChunks<'a, S> {
+    pub fn new(source: &'a S) -> Self {
+        InMemoryChunks { source }
+    }
+}
+
+impl<S: DataSourceSelf + ?Sized> DataSourceChunked for InMemoryChunks<'_, S> {
+    fn chunks<'a>(
+        &'a self,
+        columns: &[&str],
+        chunk_size: usize,
+    ) -> Result<ChunkIter<'a>, DataError> {
+        let names: Vec<String> = columns.iter().map(|name| name.to_string()).collect();
+        // columns the source can lend out are borrowed and only copied a chunk at a time;
+        // the rest (nulls, non-f64 types) have to be read filled up front
+        let data = names
+            .iter()
+            .map(|name| match self.source.get_numeric_column(name) {
+                Ok(values) => Ok(Cow::Borrowed(values)),
+                Err(_) => self.source.get_numeric_column_filled(name).map(Cow::Owned),
+            })
+            .collect::<Result<Vec<Cow<[f64]>>, _>>()?;
+        let chunk_size = chunk_size.max(1);
+        let n_rows = self.source.n_rows();
+
+        Ok(Box::new((0..n_rows).step_by(chunk_size).map(
+            move |start| {
+                let end = (start + chunk_size).min(n_rows);
+                Ok(ColumnChunk {
+                    names: names.clone(),
+                    columns: data
+                        .iter()
+                        .map(|column| column[start..end].to_vec())
+                        .collect(),
+                })
+            },
+        )))
+    }
+
+    fn has_columns(&self, name: &str) -> Result<bool, DataError> {
+        Ok(self.source.has_columns(name))
+    }
+
+    fn column_names(&self) -> Result<Vec<String>, DataError> {
+        Ok(self.source.column_names())
+    }
+}
+
+#[cfg(feature = "polars")]
+// slices of a DataFrame share its buffers, so only the current chunk is ever copied
+impl DataSourceChunked for DataFrame {
+    fn chunks<'a>(
+        &'a self,
+        columns: &[&str],
+        chunk_size: usize,
+    ) -> Result<ChunkIter<'a>, DataError> {
+        for &name in columns {
+            if !DataSourceSelf::has_columns(self, name) {
+                return Err(DataError::column_not_found(
+                    name,
+                    DataSourceSelf::column_names(self),
+                ));
+            }
+        }
+        let names: Vec<String> = columns.iter().map(|name| name.to_string()).collect();
+        let chunk_size = chunk_size.max(1);
+
+        Ok(Box::new((0..self.height()).step_by(chunk_size).map(
+            move |start| {
+                let slice = self.slice(start as i64, chunk_size);
+                ColumnChunk::from_source(&slice, &names)
+            },
+        )))
+    }
+
+    fn has_columns(&self, name: &str) -> Result<bool, DataError> {
+        Ok(DataSourceSelf::has_columns(self, name))
+    }
+
+    fn column_names(&self) -> Result<Vec<String>, DataError> {
+        Ok(DataSourceSelf::column_names(self))
+    }
+}
+
+#[cfg(feature = "polars")]
+// the query runs once on polars' streaming engine, on a thread of its own, handing over
+// batches of chunk_size rows as the iterator asks for them; at most a couple of chunks are
+// held at a time, and dropping the iterator stops the scan
+impl DataSourceChunked for LazyFrameSource {
+    fn chunks<'a>(
+        &'a self,
+        columns: &[&str],
+        chunk_size: usize,
+    ) -> Result<ChunkIter<'a>, DataError> {
+        let query = self.query(columns)?;
+        let names: Vec<String> = columns.iter().map(|name| name.to_string()).collect();
+        let chunk_size = NonZeroUsize::new(chunk_size).unwrap_or(NonZeroUsize::MIN);
+        let (sender, receiver) = mpsc::sync_channel(1);
+
+        let batches = sender.clone();
+        let callback = PlanCallback::new(move |batch: DataFrame| {
+            // true stops the query once nobody is reading
+            Ok(batches
+                .send(ColumnChunk::from_source(&batch, &names))
+                .is_err())
+        });
+        std::thread::spawn(move || {
+            let result = query
+                .sink_batches(callback, true, Some(chunk_size))
+                .and_then(|sink| sink.collect_with_engine(Engine::Streaming));
+            if let Err(err) = result {
+                let _ = sender.send(Err(err.into()));
+            }
+        });
+        Ok(Box::new(receiver.into_iter()))
+    }
+
+    fn has_columns(&self, name: &str) -> Result<bool, DataError> {
+        LazyFrameSource::has_columns(self, name)
+    }
+
+    fn column_names(&self) -> Result<Vec<String>, DataError> {
+        LazyFrameSource::column_names(self)
+    }
+}
+
+// min / max of a column seen chunk by chunk; NaN values are counted as missing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunningExtent {
+    min: f64,
+    max: f64,
+    count: usize,
+    missing: usize,
+}
+
+impl Default for RunningExtent {
+    fn default() -> Self {
+        RunningExtent {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            count: 0,
+            missing: 0,
+        }
+    }
+}
+
+impl RunningExtent {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, values: &[f64]) {
+        for &value in values {
+            if value.is_nan() {
+                self.missing += 1;
+            } else {
+                self.min = self.min.min(value);
+                self.max = self.max.max(value);
+                self.count += 1;
+            }
+        }
+    }
+
+    // combines extents accumulated separately, e.g. on different threads
+    pub fn merge(&mut self, other: &RunningExtent) {
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.count += other.count;
+        self.missing += other.missing;
+    }
+
+    // None until a non-missing value has been seen
+    pub fn range(&self) -> Option<(f64, f64)> {
+        (self.count > 0).then_some((self.min, self.max))
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn missing(&self) -> usize {
+        self.missing
+    }
+}
+
+// counts of values in equal-width bins over a fixed range, filled chunk by chunk
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunningHistogram {
+    min: f64,
+    max: f64,
+    counts: Vec<usize>,
+    // values outside [min, max] and NaN
+    skipped: usize,
+}
+
+impl RunningHistogram {
+    pub fn new(min: f64, max: f64, bins: usize) -> Self {
+        RunningHistogram {
+            min,
+            max,
+            counts: vec![0; bins.max(1)],
+            skipped: 0,
+        }
+    }
+
+    pub fn update(&mut self, values: &[f64]) {
+        let bins = self.counts.len();
+        let width = (self.max - self.min) / bins as f64;
+        for &value in values {
+            if !(self.min..=self.max).contains(&value) {
+                self.skipped += 1;
+                continue;
+            }
+            // the upper edge belongs to the last bin
+            let bin = if width > 0.0 {
+                (((value - self.min) / width) as usize).min(bins - 1)
+            } else {
+                0
+            };
+            self.counts[bin] += 1;
+        }
+    }
+
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+
+    // bins + 1 edges from min to max
+    pub fn edges(&self) -> Vec<f64> {
+        let bins = self.counts.len();
+        let width = (self.max - self.min) / bins as f64;
+        (0..=bins)
+            .map(|index| self.min + width * index as f64)
+            .collect()
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+}
+
+// one pass over a column of a chunked source
+pub fn chunked_extent<S: DataSourceChunked + ?Sized>(
+    source: &S,
+    column: &str,
+    chunk_size: usize,
+) -> Result<RunningExtent, DataError> {
+    let mut extent = RunningExtent::new();
+    for chunk in source.chunks(&[column], chunk_size)? {
+        extent.update(&chunk?.columns[0]);
+    }
+    Ok(extent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_sources::OwnedColumnSource;
+
+    #[test]
+    fn test_in_memory_chunks_and_running_stats() {
+        let source = OwnedColumnSource {
+            name: "x".to_string(),
+            data: vec![3.0, f64::NAN, 1.0, 4.0, 10.0],
+        };
+        let chunked = InMemoryChunks::new(&source);
+
+        let sizes: Vec<usize> = chunked
+            .chunks(&["x"], 2)
+            .unwrap()
+            .map(|chunk| chunk.unwrap().n_rows())
+            .collect();
+        assert_eq!(sizes, vec![2, 2, 1]);
+        let last = chunked.chunks(&["x"], 2).unwrap().last().unwrap().unwrap();
+        assert_eq!(last.column("x"), Some(&[10.0][..]));
+
+        let extent = chunked_extent(&chunked, "x", 2).unwrap();
+        assert_eq!(extent.range(), Some((1.0, 10.0)));
+        assert_eq!((extent.count(), extent.missing()), (4, 1));
+
+        let mut histogram = RunningHistogram::new(0.0, 10.0, 2);
+        for chunk in chunked.chunks(&["x"], 2).unwrap() {
+            histogram.update(chunk.unwrap().column("x").unwrap());
+        }
+        assert_eq!(histogram.counts(), &[3, 1]);
+        assert_eq!(histogram.edges(), vec![0.0, 5.0, 10.0]);
+        assert_eq!(histogram.skipped(), 1);
+
+        assert!(matches!(
+            chunked.chunks(&["y"], 2),
+            Err(DataError::ColumnNotFound { .. })
+        ));
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_polars_chunks() {
+        use polars::prelude::{DataType, IntoLazy, col, df};
+
+        let df =
+            df! { "x" => &[1.0, 2.0, 3.0, 4.0, 5.0], "y" => &[5.0, 4.0, 3.0, 2.0, 1.0] }.unwrap();
+
+        let chunks: Vec<ColumnChunk> = df
+            .chunks(&["y", "x"], 2)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[1].column("x").unwrap(), &[3.0, 4.0]);
+        assert_eq!(chunks[2].column("y").unwrap(), &[1.0]);
+
+        let lazy = LazyFrameSource::new(df.lazy());
+        let lazy_chunks: Vec<ColumnChunk> = lazy
+            .chunks(&["y", "x"], 2)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(lazy_chunks, chunks);
+        assert_eq!(
+            chunked_extent(&lazy, "x", 4).unwrap().range(),
+            Some((1.0, 5.0))
+        );
+        assert!(lazy.chunks(&["z"], 2).is_err());
+        assert!(DataSourceChunked::has_columns(&lazy, "x").unwrap());
+
+        // stopping early ends the query, and errors while it runs come out as chunks
+        let first = lazy.chunks(&["x"], 1).unwrap().next().unwrap().unwrap();
+        assert_eq!(first.column("x").unwrap(), &[1.0]);
+        let broken = LazyFrameSource::new(
+            df! { "x" => &["1", "one"] }
+                .unwrap()
+                .lazy()
+                .select([col("x").strict_cast(DataType::Float64)]),
+        );
+        let results: Vec<_> = broken.chunks(&["x"], 2).unwrap().collect();
+        assert!(matches!(results.last(), Some(Err(_))));
+    }
+}
@@ -0,0 +1,116 @@
+// A matrix of z values on a regular x/y grid (heatmaps, contours), flattened into long
+// format so it reads like any other source: one row per cell with columns x, y and z.
+// Cells are ordered row by row, x varying fastest.
+
+use crate::data_source_self::DataSourceSelf;
+use crate::data_sources::DataError;
+
+pub struct GridSource {
+    n_x: usize,
+    n_y: usize,
+    x: Vec<f64>,
+    y: Vec<f64>,
+    z: Vec<f64>,
+}
+
+const GRID_COLUMNS: [&str; 3] = ["x", "y", "z"];
+
+impl GridSource {
+    // `cell(row, column)` gives the z value; x and y are the column and row coordinates
+    pub fn from_fn(x_coords: &[f64], y_coords: &[f64], cell: impl Fn(usize, usize) -> f64) -> Self {
+        let n_cells = x_coords.len() * y_coords.len();
+        let mut x = Vec::with_capacity(n_cells);
+        let mut y = Vec::with_capacity(n_cells);
+        let mut z = Vec::with_capacity(n_cells);
+        for (row, &y_coord) in y_coords.iter().enumerate() {
+            for (column, &x_coord) in x_coords.iter().enumerate() {
+                x.push(x_coord);
+                y.push(y_coord);
+                z.push(cell(row, column));
+            }
+        }
+
+        GridSource {
+            n_x: x_coords.len(),
+            n_y: y_coords.len(),
+            x,
+            y,
+            z,
+        }
+    }
+
+    // (number of x positions, number of y positions)
+    pub fn shape(&self) -> (usize, usize) {
+        (self.n_x, self.n_y)
+    }
+}
+
+// coordinates for a grid axis: given ones must match the axis length, default is 0..n
+#[cfg(any(feature = "ndarray", feature = "nalgebra"))]
+pub(crate) fn grid_coordinates(
+    axis: &str,
+    coords: Option<&[f64]>,
+    len: usize,
+) -> Result<Vec<f64>, DataError> {
+    match coords {
+        Some(coords) if coords.len() != len => Err(DataError::LengthMismatch {
+            column: axis.to_string(),
+            expected: len,
+            actual: coords.len(),
+        }),
+        Some(coords) => Ok(coords.to_vec()),
+        None => Ok((0..len).map(|index| index as f64).collect()),
+    }
+}
+
+impl DataSourceSelf for GridSource {
+    fn get_numeric_column(&self, name: &str) -> Result<&[f64], DataError> {
+        match name {
+            "x" => Ok(&self.x),
+            "y" => Ok(&self.y),
+            "z" => Ok(&self.z),
+            _ => Err(DataError::column_not_found(name, GRID_COLUMNS)),
+        }
+    }
+
+    fn n_rows(&self) -> usize {
+        self.z.len()
+    }
+
+    fn has_columns(&self, name: &str) -> bool {
+        GRID_COLUMNS.contains(&name)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        GRID_COLUMNS.iter().map(|name| name.to_string()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_source_flattens_row_by_row() {
+        let grid = GridSource::from_fn(&[0.0, 10.0], &[1.0, 2.0, 3.0], |row, column| {
+            (row * 2 + column) as f64
+        });
+
+        assert_eq!(grid.shape(), (2, 3));
+        assert_eq!(grid.n_rows(), 6);
+        assert_eq!(
+            grid.get_numeric_column("x").unwrap(),
+            &[0.0, 10.0, 0.0, 10.0, 0.0, 10.0]
+        );
+        assert_eq!(
+            grid.get_numeric_column("y").unwrap(),
+            &[1.0, 1.0, 2.0, 2.0, 3.0, 3.0]
+        );
+        assert_eq!(
+            grid.get_numeric_column("z").unwrap(),
+            &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0]
+        );
+        assert!(grid.has_columns("z"));
+        assert!(!grid.has_columns("w"));
+    }
+}
@@ -0,0 +1,175 @@
+// Lazy polars queries as a data source. Nothing is read until a plot asks for columns,
+// and then only those columns are collected: selecting them lets polars push the
+// projection (and any filters) down into the scan, so wide files are never fully loaded.
+
+use std::path::Path;
+
+use polars::frame::DataFrame;
+use polars::prelude::{Expr, IntoLazy, LazyFrame, PlPath, ScanArgsParquet, col};
+
+use crate::data_source_self::column_type;
+use crate::data_sources::{ColumnSchema, DataError};
+
+pub struct LazyFrameSource {
+    frame: LazyFrame,
+    filters: Vec<Expr>,
+}
+
+impl LazyFrameSource {
+    pub fn new(frame: LazyFrame) -> Self {
+        LazyFrameSource {
+            frame,
+            filters: Vec::new(),
+        }
+    }
+
+    pub fn scan_parquet(path: impl AsRef<Path>) -> Result<Self, DataError> {
+        let path = PlPath::Local(path.as_ref().into());
+        let frame = LazyFrame::scan_parquet(path, ScanArgsParquet::default())?;
+        Ok(Self::new(frame))
+    }
+
+    // row filter applied before the columns are collected
+    pub fn filter(mut self, predicate: Expr) -> Self {
+        self.filters.push(predicate);
+        self
+    }
+
+    // resolving the schema only reads file metadata, not data
+    pub fn column_names(&self) -> Result<Vec<String>, DataError> {
+        let schema = self.frame.clone().collect_schema()?;
+        Ok(schema.iter_names().map(|name| name.to_string()).collect())
+    }
+
+    pub fn schema(&self) -> Result<Vec<ColumnSchema>, DataError> {
+        let schema = self.frame.clone().collect_schema()?;
+        Ok(schema
+            .iter()
+            .map(|(name, dtype)| ColumnSchema::new(name.as_str(), column_type(dtype)))
+            .collect())
+    }
+
+    pub fn has_columns(&self, name: &str) -> Result<bool, DataError> {
+        Ok(self.column_names()?.iter().any(|col_name| col_name == name))
+    }
+
+    // collect only the requested columns into an in-memory DataFrame
+    pub fn collect_columns(&self, columns: &[&str]) -> Result<DataFrame, DataError> {
+        Ok(self.query(columns)?.collect()?)
+    }
+
+    // the filtered query projected onto the requested columns, not yet collected
+    pub(crate) fn query(&self, columns: &[&str]) -> Result<LazyFrame, DataError> {
+        let available = self.column_names()?;
+        for &name in columns {
+            if !available.iter().any(|col_name| col_name == name) {
+                return Err(DataError::column_not_found(name, available));
+            }
+        }
+
+        let mut frame = self.frame.clone();
+        for predicate in &self.filters {
+            frame = frame.filter(predicate.clone());
+        }
+        let projection: Vec<Expr> = columns.iter().map(|&name| col(name)).collect();
+        Ok(frame.select(projection))
+    }
+}
+
+impl From<LazyFrame> for LazyFrameSource {
+    fn from(frame: LazyFrame) -> Self {
+        Self::new(frame)
+    }
+}
+
+impl From<DataFrame> for LazyFrameSource {
+    fn from(df: DataFrame) -> Self {
+        Self::new(df.lazy())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_expression::ColumnExpr;
+    use crate::data_source_self::DataSourceSelf;
+    use crate::data_sources::ColumnType;
+    use polars::prelude::*;
+
+    fn wide_frame() -> DataFrame {
+        df! {
+            "a" => &[1.0, 2.0, 3.0, 4.0],
+            "b" => &[10.0, 20.0, 30.0, 40.0],
+            "c" => &["w", "x", "y", "z"],
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn test_collect_columns_projects_and_filters() {
+        let source = LazyFrameSource::from(wide_frame()).filter(col("a").gt(lit(2.0)));
+
+        let df = source.collect_columns(&["b"]).unwrap();
+        assert_eq!(df.get_column_names_str(), vec!["b"]);
+        assert_eq!(df.get_numeric_column("b").unwrap(), &[30.0, 40.0]);
+
+        // unknown columns are reported before anything is collected
+        assert!(matches!(
+            source.collect_columns(&["bb"]),
+            Err(DataError::ColumnNotFound { .. })
+        ));
+    }
+
+    // a parquet file of its own for each test, so tests running in parallel don't clash
+    fn parquet_file(test: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("gracex_{}_{}.parquet", test, std::process::id()));
+        let mut df = wide_frame();
+        ParquetWriter::new(std::fs::File::create(&path).unwrap())
+            .finish(&mut df)
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_scan_parquet_reads_only_requested_columns() {
+        let path = parquet_file("scan_projection");
+        let source = LazyFrameSource::scan_parquet(&path).unwrap();
+        assert!(source.has_columns("c").unwrap());
+        assert_eq!(
+            source.schema().unwrap()[2],
+            ColumnSchema::new("c", ColumnType::Categorical)
+        );
+
+        // the optimised plan reads two of the file's three columns
+        let plan = source.query(&["a", "b"]).unwrap().explain(true).unwrap();
+        assert!(plan.contains("PROJECT 2/3 COLUMNS"), "{}", plan);
+
+        let collected = source.collect_columns(&["a", "b"]).unwrap();
+        assert_eq!(collected.n_rows(), 4);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_plots_collect_only_mapped_columns() {
+        use crate::aesthetics::aes;
+        use crate::plot::Plot;
+
+        let path = parquet_file("plot_projection");
+        // reading "broken" fails, so the plot only builds if it is never collected
+        let frame = LazyFrameSource::scan_parquet(&path)
+            .unwrap()
+            .frame
+            .with_column(col("c").strict_cast(DataType::Int64).alias("broken"));
+        let source = LazyFrameSource::new(frame).filter(col("a").gt(lit(1.0)));
+        assert!(source.collect_columns(&["broken"]).is_err());
+
+        let plot = Plot::new()
+            .lazy_data(source)
+            .geom_point(aes().x("a").y(ColumnExpr::column("b") / 10.0));
+        assert!(plot.build().is_ok());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+// nalgebra adapters: the columns of a DMatrix as a data source, and matrices as grids.
+// nalgebra stores matrices column-major already, so columns are lent without copying.
+
+use nalgebra::DMatrix;
+
+use crate::data_source_grid::{GridSource, grid_coordinates};
+use crate::data_source_self::DataSourceSelf;
+use crate::data_sources::{DataError, column_position};
+
+// columns are looked up by name, or by index ("0", "1", ...)
+pub struct MatrixColumnSource {
+    names: Vec<String>,
+    data: DMatrix<f64>,
+}
+
+impl MatrixColumnSource {
+    // columns are named after their index
+    pub fn new(data: DMatrix<f64>) -> Self {
+        let names = (0..data.ncols()).map(|index| index.to_string()).collect();
+        MatrixColumnSource { names, data }
+    }
+
+    pub fn with_names(data: DMatrix<f64>, names: &[&str]) -> Result<Self, DataError> {
+        if names.len() != data.ncols() {
+            return Err(DataError::LengthMismatch {
+                column: "names".to_string(),
+                expected: data.ncols(),
+                actual: names.len(),
+            });
+        }
+        Ok(MatrixColumnSource {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            data,
+        })
+    }
+
+    pub fn column_by_index(&self, index: usize) -> Option<&[f64]> {
+        if index >= self.data.ncols() {
+            return None;
+        }
+        let n_rows = self.data.nrows();
+        Some(&self.data.as_slice()[index * n_rows..(index + 1) * n_rows])
+    }
+}
+
+impl DataSourceSelf for MatrixColumnSource {
+    fn get_numeric_column(&self, name: &str) -> Result<&[f64], DataError> {
+        column_position(&self.names, name)
+            .and_then(|index| self.column_by_index(index))
+            .ok_or_else(|| DataError::column_not_found(name, self.names.clone()))
+    }
+
+    fn n_rows(&self) -> usize {
+        self.data.nrows()
+    }
+
+    fn has_columns(&self, name: &str) -> bool {
+        column_position(&self.names, name).is_some()
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        self.names.clone()
+    }
+}
+
+impl GridSource {
+    // z[(row, column)] sits at (x[column], y[row]); coordinates default to indices
+    pub fn from_matrix(
+        z: &DMatrix<f64>,
+        x: Option<&[f64]>,
+        y: Option<&[f64]>,
+    ) -> Result<Self, DataError> {
+        let x = grid_coordinates("x", x, z.ncols())?;
+        let y = grid_coordinates("y", y, z.nrows())?;
+        Ok(GridSource::from_fn(&x, &y, |row, column| z[(row, column)]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix_column_source_by_name_and_index() {
+        let data = DMatrix::from_row_slice(3, 2, &[1.0, 10.0, 2.0, 20.0, 3.0, 30.0]);
+
+        let named = MatrixColumnSource::with_names(data, &["time", "value"]).unwrap();
+        assert_eq!(
+            named.get_numeric_column("value").unwrap(),
+            &[10.0, 20.0, 30.0]
+        );
+        assert_eq!(named.get_numeric_column("0").unwrap(), &[1.0, 2.0, 3.0]);
+        assert_eq!(named.n_rows(), 3);
+        assert!(!named.has_columns("5"));
+
+        let z = DMatrix::from_row_slice(2, 2, &[1.0, 2.0, 3.0, 4.0]);
+        let grid = GridSource::from_matrix(&z, None, None).unwrap();
+        assert_eq!(grid.get_numeric_column("z").unwrap(), &[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(grid.get_numeric_column("x").unwrap(), &[0.0, 1.0, 0.0, 1.0]);
+    }
+}
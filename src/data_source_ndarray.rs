@@ -0,0 +1,153 @@
+// ndarray adapters: the columns of a 2D array as a data source, and 2D arrays as grids.
+
+use ndarray::{Array1, Array2, ShapeBuilder};
+
+use crate::data_source_grid::{GridSource, grid_coordinates};
+use crate::data_source_self::DataSourceSelf;
+use crate::data_sources::{DataError, column_position};
+
+// columns are looked up by name, or by index ("0", "1", ...)
+pub struct ArrayColumnSource {
+    names: Vec<String>,
+    // kept column-major, so each column is one contiguous slice
+    data: Array2<f64>,
+}
+
+impl ArrayColumnSource {
+    // columns are named after their index
+    pub fn new(data: Array2<f64>) -> Self {
+        let names = (0..data.ncols()).map(|index| index.to_string()).collect();
+        ArrayColumnSource {
+            names,
+            data: column_major(data),
+        }
+    }
+
+    pub fn with_names(data: Array2<f64>, names: &[&str]) -> Result<Self, DataError> {
+        if names.len() != data.ncols() {
+            return Err(DataError::LengthMismatch {
+                column: "names".to_string(),
+                expected: data.ncols(),
+                actual: names.len(),
+            });
+        }
+        Ok(ArrayColumnSource {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            data: column_major(data),
+        })
+    }
+
+    // a single vector as a one-column source
+    pub fn from_array1(name: &str, data: Array1<f64>) -> Self {
+        let n_rows = data.len();
+        let data = data
+            .into_shape_with_order((n_rows, 1))
+            .expect("a vector always reshapes into a single column");
+        ArrayColumnSource {
+            names: vec![name.to_string()],
+            data: column_major(data),
+        }
+    }
+
+    pub fn column_by_index(&self, index: usize) -> Option<&[f64]> {
+        if index >= self.data.ncols() {
+            return None;
+        }
+        let n_rows = self.data.nrows();
+        let values = self.data.as_slice_memory_order()?;
+        Some(&values[index * n_rows..(index + 1) * n_rows])
+    }
+}
+
+fn column_major(data: Array2<f64>) -> Array2<f64> {
+    if data.t().is_standard_layout() {
+        return data;
+    }
+    let mut stored = Array2::zeros(data.raw_dim().f());
+    stored.assign(&data);
+    stored
+}
+
+impl DataSourceSelf for ArrayColumnSource {
+    fn get_numeric_column(&self, name: &str) -> Result<&[f64], DataError> {
+        column_position(&self.names, name)
+            .and_then(|index| self.column_by_index(index))
+            .ok_or_else(|| DataError::column_not_found(name, self.names.clone()))
+    }
+
+    fn n_rows(&self) -> usize {
+        self.data.nrows()
+    }
+
+    fn has_columns(&self, name: &str) -> bool {
+        column_position(&self.names, name).is_some()
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        self.names.clone()
+    }
+}
+
+impl GridSource {
+    // z[[row, column]] sits at (x[column], y[row]); coordinates default to indices
+    pub fn from_ndarray(
+        z: &Array2<f64>,
+        x: Option<&[f64]>,
+        y: Option<&[f64]>,
+    ) -> Result<Self, DataError> {
+        let x = grid_coordinates("x", x, z.ncols())?;
+        let y = grid_coordinates("y", y, z.nrows())?;
+        Ok(GridSource::from_fn(&x, &y, |row, column| z[[row, column]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_array_column_source_by_name_and_index() {
+        let data = array![[1.0, 10.0], [2.0, 20.0], [3.0, 30.0]];
+
+        let by_index = ArrayColumnSource::new(data.clone());
+        assert_eq!(
+            by_index.get_numeric_column("1").unwrap(),
+            &[10.0, 20.0, 30.0]
+        );
+        assert_eq!(by_index.n_rows(), 3);
+
+        let named = ArrayColumnSource::with_names(data, &["time", "value"]).unwrap();
+        assert_eq!(named.get_numeric_column("time").unwrap(), &[1.0, 2.0, 3.0]);
+        assert_eq!(named.get_numeric_column("1").unwrap(), &[10.0, 20.0, 30.0]);
+        assert!(named.has_columns("value"));
+        assert!(matches!(
+            named.get_numeric_column("2"),
+            Err(DataError::ColumnNotFound { .. })
+        ));
+
+        let vector = ArrayColumnSource::from_array1("v", array![4.0, 5.0]);
+        assert_eq!(vector.get_numeric_column("v").unwrap(), &[4.0, 5.0]);
+    }
+
+    #[test]
+    fn test_grid_source_from_ndarray() {
+        let z = array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]];
+        let grid = GridSource::from_ndarray(&z, Some(&[0.0, 0.5, 1.0]), None).unwrap();
+
+        assert_eq!(grid.shape(), (3, 2));
+        assert_eq!(
+            grid.get_numeric_column("z").unwrap(),
+            &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]
+        );
+        assert_eq!(
+            grid.get_numeric_column("y").unwrap(),
+            &[0.0, 0.0, 0.0, 1.0, 1.0, 1.0]
+        );
+
+        assert!(matches!(
+            GridSource::from_ndarray(&z, Some(&[0.0]), None),
+            Err(DataError::LengthMismatch { .. })
+        ));
+    }
+}
@@ -3,7 +3,7 @@
 // 2. DataSource owns the data (we copy the data)
 // 3. DataSource trait borrows the data from DataSource object (its lifetime is dependent on self)
 
-use crate::data_sources::{DataError, OwnedColumnSource};
+use crate::data_sources::{ColumnSchema, DataError, OwnedColumnSource};
 
 // returns owned data, static lifetime
 pub trait DataSourceOwned {
@@ -12,18 +12,22 @@ pub trait DataSourceOwned {
     fn n_rows(&self) -> usize;
 
     fn has_columns(&self, name: &str) -> bool;
+
+    fn column_names(&self) -> Vec<String>;
+
+    // column names with their logical types; every column is numeric unless overridden
+    fn schema(&self) -> Vec<ColumnSchema> {
+        ColumnSchema::all_numeric(self.column_names())
+    }
 }
 
 // Trait implementations
 impl DataSourceOwned for OwnedColumnSource {
     fn get_numeric_column(&self, name: &str) -> Result<Vec<f64>, DataError> {
         if name == self.name {
-            return Ok(self.data.clone());
+            Ok(self.data.clone())
         } else {
-            return Err(DataError::ColumnNotFound(format!(
-                "Column '{}' not found",
-                name
-            )));
+            Err(DataError::column_not_found(name, [self.name.as_str()]))
         }
     }
 
@@ -34,6 +38,10 @@ impl DataSourceOwned for OwnedColumnSource {
     fn has_columns(&self, name: &str) -> bool {
         self.name == name
     }
+
+    fn column_names(&self) -> Vec<String> {
+        vec![self.name.clone()]
+    }
 }
 
 #[cfg(test)]
@@ -62,5 +70,6 @@ mod tests {
 
         // has_columns works
         assert!(owned_data_column.has_columns("positive_ints"));
+        assert_eq!(owned_data_column.column_names(), vec!["positive_ints"]);
     }
 }
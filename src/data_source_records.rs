@@ -0,0 +1,551 @@
+// Slices of plain structs as a data source: each field of a record is a named column.
+// Implement DataSourceRecord with #[derive(DataSource)] (the `derive` feature) or by hand.
+// Read straight from the records, values are gathered out of them on every call, so the
+// owned trait is used; RecordColumns gathers every column once instead, and is what a
+// plot takes.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+use crate::data_groups::DiscreteValue;
+use crate::data_source_owned::DataSourceOwned;
+use crate::data_source_self::DataSourceSelf;
+use crate::data_sources::{ColumnSchema, ColumnType, DataError};
+use crate::data_temporal::{TEMPORAL_TYPES, TemporalColumn, TemporalKind, TimeUnit};
+
+#[cfg(feature = "derive")]
+pub use gracex_derive::DataSource;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Numeric(f64),
+    Text(String),
+    Boolean(bool),
+    // days since 1970-01-01
+    Date(i64),
+    // milliseconds since the unix epoch (UTC)
+    Datetime(i64),
+    Missing,
+}
+
+pub trait DataSourceRecord {
+    // column names in field declaration order
+    fn column_names() -> Vec<&'static str>;
+
+    // value of one column for this record, None when there is no such column
+    fn field(&self, name: &str) -> Option<FieldValue>;
+}
+
+macro_rules! numeric_field_value {
+    ($($numeric:ty),*) => {
+        $(
+            impl From<$numeric> for FieldValue {
+                fn from(value: $numeric) -> Self {
+                    FieldValue::Numeric(value as f64)
+                }
+            }
+        )*
+    };
+}
+
+numeric_field_value!(f64, f32, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        FieldValue::Boolean(value)
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        FieldValue::Text(value)
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        FieldValue::Text(value.to_string())
+    }
+}
+
+impl From<NaiveDate> for FieldValue {
+    fn from(value: NaiveDate) -> Self {
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("the unix epoch is a valid date");
+        FieldValue::Date((value - epoch).num_days())
+    }
+}
+
+impl From<NaiveDateTime> for FieldValue {
+    fn from(value: NaiveDateTime) -> Self {
+        FieldValue::Datetime(value.and_utc().timestamp_millis())
+    }
+}
+
+impl From<DateTime<Utc>> for FieldValue {
+    fn from(value: DateTime<Utc>) -> Self {
+        FieldValue::Datetime(value.timestamp_millis())
+    }
+}
+
+impl<T: Into<FieldValue>> From<Option<T>> for FieldValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(FieldValue::Missing, Into::into)
+    }
+}
+
+fn record_column<T: DataSourceRecord>(
+    records: &[T],
+    name: &str,
+) -> Result<Vec<FieldValue>, DataError> {
+    if !T::column_names().contains(&name) {
+        return Err(DataError::column_not_found(name, T::column_names()));
+    }
+    Ok(records
+        .iter()
+        .map(|record| record.field(name).unwrap_or(FieldValue::Missing))
+        .collect())
+}
+
+// missing values come out as NaN, booleans as 0 / 1
+fn numeric_values(name: &str, values: Vec<FieldValue>) -> Result<Vec<f64>, DataError> {
+    values
+        .into_iter()
+        .map(|value| match value {
+            FieldValue::Numeric(number) => Ok(number),
+            FieldValue::Boolean(flag) => Ok(f64::from(u8::from(flag))),
+            FieldValue::Missing => Ok(f64::NAN),
+            other => Err(DataError::type_mismatch(
+                name,
+                "numeric",
+                field_type_name(&other),
+            )),
+        })
+        .collect()
+}
+
+impl<T: DataSourceRecord> DataSourceOwned for [T] {
+    fn get_numeric_column(&self, name: &str) -> Result<Vec<f64>, DataError> {
+        numeric_values(name, record_column(self, name)?)
+    }
+
+    fn n_rows(&self) -> usize {
+        self.len()
+    }
+
+    fn has_columns(&self, name: &str) -> bool {
+        T::column_names().contains(&name)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        T::column_names().into_iter().map(String::from).collect()
+    }
+
+    // the type of a column is taken from its first value present, numeric when all missing
+    fn schema(&self) -> Vec<ColumnSchema> {
+        T::column_names()
+            .into_iter()
+            .map(|name| {
+                let column_type = self
+                    .iter()
+                    .filter_map(|record| record.field(name))
+                    .find(|value| *value != FieldValue::Missing)
+                    .map_or(ColumnType::Numeric, |value| field_column_type(&value));
+                ColumnSchema::new(name, column_type)
+            })
+            .collect()
+    }
+}
+
+impl<T: DataSourceRecord> DataSourceOwned for Vec<T> {
+    fn get_numeric_column(&self, name: &str) -> Result<Vec<f64>, DataError> {
+        self.as_slice().get_numeric_column(name)
+    }
+
+    fn n_rows(&self) -> usize {
+        self.len()
+    }
+
+    fn has_columns(&self, name: &str) -> bool {
+        self.as_slice().has_columns(name)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        self.as_slice().column_names()
+    }
+
+    fn schema(&self) -> Vec<ColumnSchema> {
+        self.as_slice().schema()
+    }
+}
+
+// string fields, missing values as None
+pub fn text_column<T: DataSourceRecord>(
+    records: &[T],
+    name: &str,
+) -> Result<Vec<Option<String>>, DataError> {
+    text_values(name, record_column(records, name)?)
+}
+
+fn text_values(name: &str, values: Vec<FieldValue>) -> Result<Vec<Option<String>>, DataError> {
+    values
+        .into_iter()
+        .map(|value| match value {
+            FieldValue::Text(text) => Ok(Some(text)),
+            FieldValue::Missing => Ok(None),
+            other => Err(DataError::type_mismatch(
+                name,
+                "string",
+                field_type_name(&other),
+            )),
+        })
+        .collect()
+}
+
+// date / datetime fields; the kind is taken from the first value present
+pub fn temporal_column<T: DataSourceRecord>(
+    records: &[T],
+    name: &str,
+) -> Result<TemporalColumn, DataError> {
+    temporal_values(name, record_column(records, name)?)
+}
+
+fn temporal_values(name: &str, values: Vec<FieldValue>) -> Result<TemporalColumn, DataError> {
+    let kind = match values.iter().find(|value| **value != FieldValue::Missing) {
+        Some(FieldValue::Datetime(_)) => TemporalKind::Datetime {
+            unit: TimeUnit::Milliseconds,
+            time_zone: None,
+        },
+        _ => TemporalKind::Date,
+    };
+
+    let values = values
+        .into_iter()
+        .map(|value| match (&kind, value) {
+            (TemporalKind::Date, FieldValue::Date(days)) => Ok(Some(days)),
+            (TemporalKind::Datetime { .. }, FieldValue::Datetime(millis)) => Ok(Some(millis)),
+            (_, FieldValue::Missing) => Ok(None),
+            (_, other) => Err(DataError::type_mismatch(
+                name,
+                TEMPORAL_TYPES,
+                field_type_name(&other),
+            )),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(TemporalColumn { kind, values })
+}
+
+// every column of the records gathered once, typed by its first value present (numeric
+// when all missing), so it can be read, grouped and plotted like any other source
+pub struct RecordColumns {
+    n_rows: usize,
+    columns: Vec<(String, RecordColumn)>,
+}
+
+enum RecordColumn {
+    // booleans are kept as 0 / 1, missing values as NaN
+    Numeric { values: Vec<f64>, boolean: bool },
+    Text(Vec<Option<String>>),
+    Temporal(TemporalColumn),
+}
+
+impl RecordColumn {
+    fn column_type(&self) -> ColumnType {
+        match self {
+            RecordColumn::Numeric { boolean: false, .. } => ColumnType::Numeric,
+            RecordColumn::Numeric { boolean: true, .. } => ColumnType::Boolean,
+            RecordColumn::Text(_) => ColumnType::Categorical,
+            RecordColumn::Temporal(_) => ColumnType::Temporal,
+        }
+    }
+}
+
+impl RecordColumns {
+    // fails when a column mixes types, e.g. text in a numeric field of a hand-written record
+    pub fn new<T: DataSourceRecord>(records: &[T]) -> Result<Self, DataError> {
+        let columns = T::column_names()
+            .into_iter()
+            .map(|name| {
+                let values = record_column(records, name)?;
+                let column_type = values
+                    .iter()
+                    .find(|value| **value != FieldValue::Missing)
+                    .map_or(ColumnType::Numeric, field_column_type);
+                let column = match column_type {
+                    ColumnType::Categorical => RecordColumn::Text(text_values(name, values)?),
+                    ColumnType::Temporal => RecordColumn::Temporal(temporal_values(name, values)?),
+                    _ => RecordColumn::Numeric {
+                        values: numeric_values(name, values)?,
+                        boolean: column_type == ColumnType::Boolean,
+                    },
+                };
+                Ok((name.to_string(), column))
+            })
+            .collect::<Result<_, DataError>>()?;
+        Ok(RecordColumns {
+            n_rows: records.len(),
+            columns,
+        })
+    }
+
+    fn column(&self, name: &str) -> Result<&RecordColumn, DataError> {
+        self.columns
+            .iter()
+            .find(|(column_name, _)| column_name == name)
+            .map(|(_, column)| column)
+            .ok_or_else(|| DataError::column_not_found(name, self.column_names()))
+    }
+}
+
+impl DataSourceSelf for RecordColumns {
+    fn get_numeric_column(&self, name: &str) -> Result<&[f64], DataError> {
+        match self.column(name)? {
+            RecordColumn::Numeric { values, .. } => Ok(values),
+            other => Err(DataError::type_mismatch(
+                name,
+                "numeric",
+                other.column_type().name(),
+            )),
+        }
+    }
+
+    fn n_rows(&self) -> usize {
+        self.n_rows
+    }
+
+    fn has_columns(&self, name: &str) -> bool {
+        self.columns
+            .iter()
+            .any(|(column_name, _)| column_name == name)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        self.columns.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    fn schema(&self) -> Vec<ColumnSchema> {
+        self.columns
+            .iter()
+            .map(|(name, column)| ColumnSchema::new(name.as_str(), column.column_type()))
+            .collect()
+    }
+
+    fn get_temporal_column(&self, name: &str) -> Result<TemporalColumn, DataError> {
+        match self.column(name)? {
+            RecordColumn::Temporal(column) => Ok(column.clone()),
+            other => Err(DataError::type_mismatch(
+                name,
+                TEMPORAL_TYPES,
+                other.column_type().name(),
+            )),
+        }
+    }
+
+    // dates and datetimes as their text, like a DataFrame's
+    fn get_discrete_column(&self, name: &str) -> Result<Vec<DiscreteValue>, DataError> {
+        let values = match self.column(name)? {
+            RecordColumn::Numeric {
+                values,
+                boolean: false,
+            } => values
+                .iter()
+                .map(|&value| DiscreteValue::from(value))
+                .collect(),
+            RecordColumn::Numeric {
+                values,
+                boolean: true,
+            } => values
+                .iter()
+                .map(|&value| match value.is_nan() {
+                    true => DiscreteValue::Missing,
+                    false => DiscreteValue::Boolean(value == 1.0),
+                })
+                .collect(),
+            RecordColumn::Text(values) => values
+                .iter()
+                .map(|value| {
+                    value
+                        .clone()
+                        .map_or(DiscreteValue::Missing, DiscreteValue::Text)
+                })
+                .collect(),
+            RecordColumn::Temporal(column) => column
+                .values
+                .iter()
+                .map(|value| {
+                    value
+                        .and_then(|value| temporal_text(&column.kind, value))
+                        .map_or(DiscreteValue::Missing, DiscreteValue::Text)
+                })
+                .collect(),
+        };
+        Ok(values)
+    }
+}
+
+fn temporal_text(kind: &TemporalKind, value: i64) -> Option<String> {
+    match kind {
+        TemporalKind::Date => {
+            let epoch = NaiveDate::from_ymd_opt(1970, 1, 1)?;
+            epoch
+                .checked_add_signed(chrono::Duration::days(value))
+                .map(|date| date.to_string())
+        }
+        _ => {
+            DateTime::from_timestamp_millis(value).map(|datetime| datetime.naive_utc().to_string())
+        }
+    }
+}
+
+fn field_column_type(value: &FieldValue) -> ColumnType {
+    match value {
+        FieldValue::Numeric(_) | FieldValue::Missing => ColumnType::Numeric,
+        FieldValue::Text(_) => ColumnType::Categorical,
+        FieldValue::Boolean(_) => ColumnType::Boolean,
+        FieldValue::Date(_) | FieldValue::Datetime(_) => ColumnType::Temporal,
+    }
+}
+
+fn field_type_name(value: &FieldValue) -> &'static str {
+    match value {
+        FieldValue::Numeric(_) => "numeric",
+        FieldValue::Text(_) => "string",
+        FieldValue::Boolean(_) => "bool",
+        FieldValue::Date(_) => "date",
+        FieldValue::Datetime(_) => "datetime",
+        FieldValue::Missing => "null",
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use super::*;
+
+    #[derive(DataSource)]
+    struct Observation {
+        age: u32,
+        #[data_source(rename = "height_cm")]
+        height: f64,
+        name: String,
+        visited: NaiveDate,
+        weight: Option<f32>,
+        #[data_source(skip)]
+        #[allow(dead_code)]
+        notes: Vec<String>,
+    }
+
+    fn observations() -> Vec<Observation> {
+        vec![
+            Observation {
+                age: 30,
+                height: 170.0,
+                name: "ada".to_string(),
+                visited: NaiveDate::from_ymd_opt(1970, 1, 2).unwrap(),
+                weight: Some(60.0),
+                notes: Vec::new(),
+            },
+            Observation {
+                age: 40,
+                height: 180.0,
+                name: "bob".to_string(),
+                visited: NaiveDate::from_ymd_opt(1970, 1, 11).unwrap(),
+                weight: None,
+                notes: Vec::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_derived_columns() {
+        assert_eq!(
+            Observation::column_names(),
+            vec!["age", "height_cm", "name", "visited", "weight"]
+        );
+
+        let records = observations();
+        assert_eq!(records.get_numeric_column("age").unwrap(), vec![30.0, 40.0]);
+        assert_eq!(
+            records.get_numeric_column("height_cm").unwrap(),
+            vec![170.0, 180.0]
+        );
+        assert_eq!(records.n_rows(), 2);
+
+        // renamed and skipped fields are not reachable under their field names
+        assert!(!records.has_columns("height"));
+        assert!(!records.has_columns("notes"));
+        assert!(matches!(
+            records.get_numeric_column("notes"),
+            Err(DataError::ColumnNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_derived_text_temporal_and_missing_columns() {
+        let records = observations();
+
+        let weight = records.get_numeric_column("weight").unwrap();
+        assert_eq!(weight[0], 60.0);
+        assert!(weight[1].is_nan());
+
+        assert_eq!(
+            text_column(&records, "name").unwrap(),
+            vec![Some("ada".to_string()), Some("bob".to_string())]
+        );
+        assert!(matches!(
+            records.get_numeric_column("name"),
+            Err(DataError::TypeMismatch { .. })
+        ));
+
+        let visited = temporal_column(&records, "visited").unwrap();
+        assert_eq!(visited.kind, TemporalKind::Date);
+        assert_eq!(visited.values, vec![Some(1), Some(10)]);
+
+        let column_types: Vec<_> = records
+            .schema()
+            .into_iter()
+            .map(|column| column.column_type)
+            .collect();
+        assert_eq!(
+            column_types,
+            vec![
+                ColumnType::Numeric,
+                ColumnType::Numeric,
+                ColumnType::Categorical,
+                ColumnType::Temporal,
+                ColumnType::Numeric,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_columns_are_gathered_once_and_plotted() {
+        use crate::aesthetics::aes;
+        use crate::plot::Plot;
+
+        let columns = RecordColumns::new(&observations()).unwrap();
+        assert_eq!(DataSourceSelf::schema(&columns), observations().schema());
+        assert_eq!(
+            DataSourceSelf::get_numeric_column(&columns, "height_cm").unwrap(),
+            &[170.0, 180.0]
+        );
+        assert_eq!(
+            columns.get_discrete_column("name").unwrap(),
+            vec!["ada".into(), "bob".into()]
+        );
+        assert_eq!(
+            columns.get_discrete_column("visited").unwrap(),
+            vec!["1970-01-02".into(), "1970-01-11".into()]
+        );
+        assert_eq!(
+            columns.get_temporal_column("visited").unwrap(),
+            temporal_column(&observations(), "visited").unwrap()
+        );
+        assert!(matches!(
+            DataSourceSelf::get_numeric_column(&columns, "name"),
+            Err(DataError::TypeMismatch { .. })
+        ));
+
+        let plot = Plot::new()
+            .data(columns)
+            .geom_point(aes().x("visited").y("height_cm").color("name"));
+        assert!(plot.build().is_ok());
+    }
+}
@@ -1,8 +1,22 @@
+#[cfg(feature = "polars")]
 use polars::frame::DataFrame;
+#[cfg(feature = "polars")]
 use polars::prelude::DataType::Float64;
+#[cfg(feature = "polars")]
+use polars::prelude::{
+    Column, DataType, Expr, Float64Chunked, GroupsIndicator, IdxCa, IdxSize, IntoLazy,
+    TimeUnit as PolarsTimeUnit,
+};
+#[cfg(feature = "polars")]
 use polars::series::Series;
 
-use crate::data_sources::{DataError, OwnedColumnSource};
+use crate::data_groups::{DiscreteValue, GroupIndices, group_rows_by_value};
+#[cfg(feature = "polars")]
+use crate::data_sources::ColumnType;
+use crate::data_sources::{ColumnSchema, DataError, OwnedColumnSource};
+use crate::data_temporal::{TEMPORAL_TYPES, TemporalColumn};
+#[cfg(feature = "polars")]
+use crate::data_temporal::{TemporalKind, TimeUnit};
 
 // gets data from self (lifetime pinned to self)
 pub trait DataSourceSelf {
@@ -11,17 +25,149 @@ pub trait DataSourceSelf {
     fn n_rows(&self) -> usize;
 
     fn has_columns(&self, name: &str) -> bool;
+
+    fn column_names(&self) -> Vec<String>;
+
+    // column names with their logical types; every column is numeric unless overridden.
+    // DataFrame and RecordBatch have an inherent schema() too, so call
+    // DataSourceSelf::schema(&df) on those
+    fn schema(&self) -> Vec<ColumnSchema> {
+        ColumnSchema::all_numeric(self.column_names())
+    }
+
+    // values with missing entries (nulls) replaced by NaN, so a column with gaps can still be read
+    fn get_numeric_column_filled(&self, name: &str) -> Result<Vec<f64>, DataError> {
+        self.get_numeric_column(name).map(|data| data.to_vec())
+    }
+
+    // true for every row holding a usable value (neither null nor NaN)
+    fn validity_mask(&self, name: &str) -> Result<Vec<bool>, DataError> {
+        let data = self.get_numeric_column_filled(name)?;
+        Ok(data.iter().map(|value| !value.is_nan()).collect())
+    }
+
+    // date, datetime and duration columns, with their units preserved; sources with
+    // temporal columns override this, the rest report the column's logical type
+    fn get_temporal_column(&self, name: &str) -> Result<TemporalColumn, DataError> {
+        match self.schema().into_iter().find(|column| column.name == name) {
+            Some(column) => Err(DataError::type_mismatch(
+                name,
+                TEMPORAL_TYPES,
+                column.column_type.name(),
+            )),
+            None => Err(DataError::column_not_found(name, self.column_names())),
+        }
+    }
+
+    // values of a column used for grouping; numeric columns by default, NaN as missing
+    fn get_discrete_column(&self, name: &str) -> Result<Vec<DiscreteValue>, DataError> {
+        let data = self.get_numeric_column_filled(name)?;
+        Ok(data.into_iter().map(DiscreteValue::from).collect())
+    }
+
+    // for columns whose levels have an order of their own (categorical and enum columns),
+    // each row's place in that order; None for columns whose values sort by themselves
+    fn get_level_codes(&self, name: &str) -> Result<Option<Vec<Option<u32>>>, DataError> {
+        match self.has_columns(name) {
+            true => Ok(None),
+            false => Err(DataError::column_not_found(name, self.column_names())),
+        }
+    }
+
+    // row indices of every distinct combination of the columns' values
+    fn group_indices(&self, columns: &[&str]) -> Result<GroupIndices, DataError> {
+        group_rows_by_value(self, columns)
+    }
+
+    // evaluates a polars expression to one value per row; only polars-backed sources can
+    #[cfg(feature = "polars")]
+    fn evaluate_polars_expr(&self, expr: &Expr) -> Result<Vec<f64>, DataError> {
+        Err(DataError::InvalidExpression {
+            expression: expr.to_string(),
+            message: "polars expressions need a DataFrame source".to_string(),
+        })
+    }
+
+    // evaluates a polars expression over the given rows only, so aggregations and window
+    // functions see just those rows; polars-backed sources override this
+    #[cfg(feature = "polars")]
+    fn evaluate_polars_expr_on_rows(
+        &self,
+        expr: &Expr,
+        rows: &[usize],
+    ) -> Result<Vec<f64>, DataError> {
+        let values = self.evaluate_polars_expr(expr)?;
+        Ok(rows.iter().map(|&row| values[row]).collect())
+    }
+}
+
+// a borrowed source reads like the source itself, so plots can take &df
+impl<S: DataSourceSelf + ?Sized> DataSourceSelf for &S {
+    fn get_numeric_column(&self, name: &str) -> Result<&[f64], DataError> {
+        (**self).get_numeric_column(name)
+    }
+
+    fn n_rows(&self) -> usize {
+        (**self).n_rows()
+    }
+
+    fn has_columns(&self, name: &str) -> bool {
+        (**self).has_columns(name)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        (**self).column_names()
+    }
+
+    fn schema(&self) -> Vec<ColumnSchema> {
+        (**self).schema()
+    }
+
+    fn get_numeric_column_filled(&self, name: &str) -> Result<Vec<f64>, DataError> {
+        (**self).get_numeric_column_filled(name)
+    }
+
+    fn validity_mask(&self, name: &str) -> Result<Vec<bool>, DataError> {
+        (**self).validity_mask(name)
+    }
+
+    fn get_temporal_column(&self, name: &str) -> Result<TemporalColumn, DataError> {
+        (**self).get_temporal_column(name)
+    }
+
+    fn get_discrete_column(&self, name: &str) -> Result<Vec<DiscreteValue>, DataError> {
+        (**self).get_discrete_column(name)
+    }
+
+    fn get_level_codes(&self, name: &str) -> Result<Option<Vec<Option<u32>>>, DataError> {
+        (**self).get_level_codes(name)
+    }
+
+    fn group_indices(&self, columns: &[&str]) -> Result<GroupIndices, DataError> {
+        (**self).group_indices(columns)
+    }
+
+    #[cfg(feature = "polars")]
+    fn evaluate_polars_expr(&self, expr: &Expr) -> Result<Vec<f64>, DataError> {
+        (**self).evaluate_polars_expr(expr)
+    }
+
+    #[cfg(feature = "polars")]
+    fn evaluate_polars_expr_on_rows(
+        &self,
+        expr: &Expr,
+        rows: &[usize],
+    ) -> Result<Vec<f64>, DataError> {
+        (**self).evaluate_polars_expr_on_rows(expr, rows)
+    }
 }
 
 impl DataSourceSelf for OwnedColumnSource {
     fn get_numeric_column(&self, name: &str) -> Result<&[f64], DataError> {
         if name == self.name {
-            return Ok(&self.data);
+            Ok(&self.data)
         } else {
-            return Err(DataError::ColumnNotFound(format!(
-                "Column '{}' not found",
-                name
-            )));
+            Err(DataError::column_not_found(name, [self.name.as_str()]))
         }
     }
 
@@ -32,8 +178,13 @@ impl DataSourceSelf for OwnedColumnSource {
     fn has_columns(&self, name: &str) -> bool {
         self.name == name
     }
+
+    fn column_names(&self) -> Vec<String> {
+        vec![self.name.clone()]
+    }
 }
 
+#[cfg(feature = "polars")]
 impl DataSourceSelf for DataFrame {
     fn has_columns(&self, name: &str) -> bool {
         let column_names = self.get_column_names();
@@ -44,19 +195,105 @@ impl DataSourceSelf for DataFrame {
         self.height()
     }
 
+    fn column_names(&self) -> Vec<String> {
+        self.get_column_names_str()
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    fn schema(&self) -> Vec<ColumnSchema> {
+        self.get_columns()
+            .iter()
+            .map(|column| ColumnSchema::new(column.name().as_str(), column_type(column.dtype())))
+            .collect()
+    }
+
     fn get_numeric_column(&self, name: &str) -> Result<&[f64], DataError> {
-        let column = self.column(name)?;
+        let column = frame_column(self, name)?;
         if column.dtype() != &Float64 {
-            return Err(DataError::TypeMismatch(format!(
-                "Column '{}' is not f64",
-                name
-            )));
+            Err(DataError::type_mismatch(name, "f64", column.dtype()))
         } else {
             column.f64()?.cont_slice().map_err(Into::into)
         }
     }
+
+    fn get_numeric_column_filled(&self, name: &str) -> Result<Vec<f64>, DataError> {
+        let column = frame_column(self, name)?;
+        float_values_from_series(column.as_materialized_series(), name)
+    }
+
+    fn get_temporal_column(&self, name: &str) -> Result<TemporalColumn, DataError> {
+        let column = frame_column(self, name)?;
+        temporal_from_series(column.as_materialized_series(), name)
+    }
+
+    fn get_discrete_column(&self, name: &str) -> Result<Vec<DiscreteValue>, DataError> {
+        let column = frame_column(self, name)?;
+        discrete_from_series(column.as_materialized_series(), name)
+    }
+
+    fn get_level_codes(&self, name: &str) -> Result<Option<Vec<Option<u32>>>, DataError> {
+        level_codes_from_series(frame_column(self, name)?.as_materialized_series())
+    }
+
+    fn group_indices(&self, columns: &[&str]) -> Result<GroupIndices, DataError> {
+        let key_columns = columns
+            .iter()
+            .map(|&name| self.get_discrete_column(name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let group_by = self.group_by(columns.iter().copied())?;
+        let groups = group_by
+            .get_groups()
+            .iter()
+            .map(|group| {
+                let mut rows: Vec<usize> = match group {
+                    GroupsIndicator::Idx((_, rows)) => {
+                        rows.iter().map(|&row| row as usize).collect()
+                    }
+                    GroupsIndicator::Slice([first, len]) => {
+                        (first as usize..(first + len) as usize).collect()
+                    }
+                };
+                rows.sort_unstable();
+                let keys = key_columns
+                    .iter()
+                    .map(|column| column[rows[0]].clone())
+                    .collect();
+                (keys, rows)
+            })
+            .collect();
+        Ok(groups)
+    }
+
+    fn evaluate_polars_expr(&self, expr: &Expr) -> Result<Vec<f64>, DataError> {
+        let result = self
+            .clone()
+            .lazy()
+            .select([expr.clone().cast(Float64)])
+            .collect()?;
+        let column = &result.get_columns()[0];
+        let values = fill_nulls_with_nan(column.f64()?);
+        // aggregations come back as a single value, spread it over every row
+        match values.as_slice() {
+            [value] if self.height() != 1 => Ok(vec![*value; self.height()]),
+            _ => Ok(values),
+        }
+    }
+
+    fn evaluate_polars_expr_on_rows(
+        &self,
+        expr: &Expr,
+        rows: &[usize],
+    ) -> Result<Vec<f64>, DataError> {
+        let rows: Vec<IdxSize> = rows.iter().map(|&row| row as IdxSize).collect();
+        let subset = self.take(&IdxCa::from_vec("rows".into(), rows))?;
+        subset.evaluate_polars_expr(expr)
+    }
 }
 
+#[cfg(feature = "polars")]
 impl DataSourceSelf for Series {
     fn has_columns(&self, name: &str) -> bool {
         let column_name = self.name();
@@ -67,19 +304,182 @@ impl DataSourceSelf for Series {
         self.len()
     }
 
+    fn column_names(&self) -> Vec<String> {
+        vec![self.name().to_string()]
+    }
+
+    fn schema(&self) -> Vec<ColumnSchema> {
+        vec![ColumnSchema::new(
+            self.name().as_str(),
+            column_type(self.dtype()),
+        )]
+    }
+
     fn get_numeric_column(&self, name: &str) -> Result<&[f64], DataError> {
         if self.dtype() != &Float64 {
-            return Err(DataError::TypeMismatch(format!(
-                "Column '{}' is not f64",
-                name
-            )));
+            Err(DataError::type_mismatch(name, "f64", self.dtype()))
         } else {
             self.f64()?.cont_slice().map_err(Into::into)
         }
     }
+
+    fn get_numeric_column_filled(&self, name: &str) -> Result<Vec<f64>, DataError> {
+        float_values_from_series(self, name)
+    }
+
+    fn get_temporal_column(&self, name: &str) -> Result<TemporalColumn, DataError> {
+        temporal_from_series(self, name)
+    }
+
+    fn get_discrete_column(&self, name: &str) -> Result<Vec<DiscreteValue>, DataError> {
+        discrete_from_series(self, name)
+    }
+
+    fn get_level_codes(&self, name: &str) -> Result<Option<Vec<Option<u32>>>, DataError> {
+        match self.has_columns(name) {
+            true => level_codes_from_series(self),
+            false => Err(DataError::column_not_found(name, [self.name().as_str()])),
+        }
+    }
+}
+
+#[cfg(feature = "polars")]
+// polars reports a missing column without listing the alternatives, so look it up ourselves
+fn frame_column<'a>(df: &'a DataFrame, name: &str) -> Result<&'a Column, DataError> {
+    df.column(name)
+        .map_err(|_| DataError::column_not_found(name, df.get_column_names_str()))
+}
+
+#[cfg(feature = "polars")]
+// polars keeps nulls in a separate bitmap, flatten them into NaN
+fn fill_nulls_with_nan(column: &Float64Chunked) -> Vec<f64> {
+    column
+        .iter()
+        .map(|value| value.unwrap_or(f64::NAN))
+        .collect()
+}
+
+// any primitive numeric series as f64s, integers and f32s are cast like in expressions
+#[cfg(feature = "polars")]
+fn float_values_from_series(series: &Series, name: &str) -> Result<Vec<f64>, DataError> {
+    let dtype = series.dtype();
+    if dtype == &Float64 {
+        Ok(fill_nulls_with_nan(series.f64()?))
+    } else if dtype.is_primitive_numeric() {
+        Ok(fill_nulls_with_nan(series.cast(&Float64)?.f64()?))
+    } else {
+        Err(DataError::type_mismatch(name, "numeric", dtype))
+    }
+}
+
+#[cfg(feature = "polars")]
+fn temporal_from_series(series: &Series, name: &str) -> Result<TemporalColumn, DataError> {
+    let kind = match series.dtype() {
+        DataType::Date => TemporalKind::Date,
+        DataType::Datetime(unit, time_zone) => TemporalKind::Datetime {
+            unit: time_unit_from_polars(unit),
+            time_zone: time_zone.as_ref().map(|zone| zone.to_string()),
+        },
+        DataType::Duration(unit) => TemporalKind::Duration {
+            unit: time_unit_from_polars(unit),
+        },
+        other => {
+            return Err(DataError::type_mismatch(name, TEMPORAL_TYPES, other));
+        }
+    };
+
+    // the physical representation is the integer count of days or ticks
+    let physical = series.to_physical_repr().cast(&DataType::Int64)?;
+    let values = physical.i64()?.iter().collect();
+    Ok(TemporalColumn { kind, values })
+}
+
+// the codes of categorical and enum values: enum levels are in their declared order,
+// categories in the order polars met them
+#[cfg(feature = "polars")]
+fn level_codes_from_series(series: &Series) -> Result<Option<Vec<Option<u32>>>, DataError> {
+    let mapping = match series.dtype() {
+        DataType::Categorical(_, mapping) | DataType::Enum(_, mapping) => mapping.clone(),
+        _ => return Ok(None),
+    };
+    let values = series.cast(&DataType::String)?;
+    let codes = values.str()?.iter();
+    Ok(Some(
+        codes
+            .map(|value| value.and_then(|text| mapping.get_cat(text)))
+            .collect(),
+    ))
 }
+
+#[cfg(feature = "polars")]
+// strings and booleans keep their values, categoricals and temporals are grouped by
+// their text representation, other numbers as f64
+fn discrete_from_series(series: &Series, name: &str) -> Result<Vec<DiscreteValue>, DataError> {
+    let dtype = series.dtype();
+    if dtype == &DataType::Boolean {
+        let values = series.bool()?.iter();
+        return Ok(values
+            .map(|value| value.map_or(DiscreteValue::Missing, DiscreteValue::Boolean))
+            .collect());
+    }
+    if dtype.is_primitive_numeric() {
+        let values = series.cast(&Float64)?;
+        return Ok(values
+            .f64()?
+            .iter()
+            .map(|value| value.map_or(DiscreteValue::Missing, DiscreteValue::from))
+            .collect());
+    }
+    if dtype == &DataType::String
+        || dtype.is_temporal()
+        || dtype.is_categorical()
+        || dtype.is_enum()
+    {
+        let values = series.cast(&DataType::String)?;
+        return Ok(values
+            .str()?
+            .iter()
+            .map(|value| {
+                value.map_or(DiscreteValue::Missing, |text| {
+                    DiscreteValue::Text(text.to_string())
+                })
+            })
+            .collect());
+    }
+    Err(DataError::type_mismatch(
+        name,
+        "string, boolean, categorical, temporal or numeric",
+        dtype,
+    ))
+}
+
+#[cfg(feature = "polars")]
+pub(crate) fn column_type(dtype: &DataType) -> ColumnType {
+    if dtype.is_primitive_numeric() {
+        ColumnType::Numeric
+    } else if dtype.is_temporal() {
+        ColumnType::Temporal
+    } else if dtype == &DataType::Boolean {
+        ColumnType::Boolean
+    } else {
+        ColumnType::Categorical
+    }
+}
+
+#[cfg(feature = "polars")]
+fn time_unit_from_polars(unit: &PolarsTimeUnit) -> TimeUnit {
+    match unit {
+        PolarsTimeUnit::Nanoseconds => TimeUnit::Nanoseconds,
+        PolarsTimeUnit::Microseconds => TimeUnit::Microseconds,
+        PolarsTimeUnit::Milliseconds => TimeUnit::Milliseconds,
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*; // Imports everything from the outer file
+    #[cfg(feature = "polars")]
+    use polars::prelude::NamedFrom;
 
     #[test]
     // test data source owned for owned_column_source
@@ -100,15 +500,113 @@ mod tests {
         assert_eq!(number_rows, test_data_clone.len());
 
         assert!(owned_column.has_columns("positive_ints"));
+
+        // not temporal, and the error says what the column is instead
+        let Err(DataError::TypeMismatch { actual, .. }) =
+            owned_column.get_temporal_column("positive_ints")
+        else {
+            panic!("expected a type mismatch");
+        };
+        assert_eq!(actual, "numeric");
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_data_source_self_for_data_frame_with_nulls() {
+        let df = DataFrame::new(vec![
+            Series::new("x".into(), &[Some(1.0), None, Some(f64::NAN)]).into(),
+        ])
+        .unwrap();
+
+        // contiguous access fails on nulls, filled access does not
+        assert!(df.get_numeric_column("x").is_err());
+
+        let filled = df.get_numeric_column_filled("x").unwrap();
+        assert_eq!(filled[0], 1.0);
+        assert!(filled[1].is_nan());
+        assert!(filled[2].is_nan());
+
+        assert_eq!(df.validity_mask("x").unwrap(), vec![true, false, false]);
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_data_source_self_temporal_columns() {
+        let dates = Series::new("day".into(), &[0i32, 19_000])
+            .cast(&DataType::Date)
+            .unwrap();
+        let stamps = Series::new("stamp".into(), &[Some(1_000i64), None])
+            .cast(&DataType::Datetime(
+                PolarsTimeUnit::Milliseconds,
+                Some(polars::prelude::TimeZone::UTC),
+            ))
+            .unwrap();
+        let df = DataFrame::new(vec![dates.clone().into(), stamps.into()]).unwrap();
+
+        let day = df.get_temporal_column("day").unwrap();
+        assert_eq!(day.kind, TemporalKind::Date);
+        assert_eq!(day.values, vec![Some(0), Some(19_000)]);
+
+        let stamp = df.get_temporal_column("stamp").unwrap();
+        assert_eq!(
+            stamp.kind,
+            TemporalKind::Datetime {
+                unit: TimeUnit::Milliseconds,
+                time_zone: Some("UTC".to_string()),
+            }
+        );
+        assert_eq!(stamp.values, vec![Some(1_000), None]);
+
+        // series work the same way, numeric columns are rejected
+        assert_eq!(dates.get_temporal_column("day").unwrap(), day);
+        let numbers = Series::new("x".into(), &[1.0, 2.0]);
+        assert!(matches!(
+            df.get_temporal_column("dya"),
+            Err(DataError::ColumnNotFound { .. })
+        ));
+        assert!(matches!(
+            numbers.get_temporal_column("x"),
+            Err(DataError::TypeMismatch { .. })
+        ));
     }
 
-    // #[test]
-    // fn test_data_source_self_for_data_frame() {
-    //     todo!()
-    // }
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_data_source_self_schema() {
+        use polars::prelude::df;
+
+        let df = df! {
+            "name" => &["a", "b"],
+            "count" => &[1i64, 2],
+            "flag" => &[true, false],
+        }
+        .unwrap();
+
+        assert_eq!(df.column_names(), vec!["name", "count", "flag"]);
+        assert_eq!(
+            DataSourceSelf::schema(&df),
+            vec![
+                ColumnSchema::new("name", ColumnType::Categorical),
+                ColumnSchema::new("count", ColumnType::Numeric),
+                ColumnSchema::new("flag", ColumnType::Boolean),
+            ]
+        );
 
-    // #[test]
-    // fn test_data_source_self_for_series() {
-    //     todo!()
-    // }
+        // the default temporal getter lists the alternatives too
+        let owned = OwnedColumnSource {
+            name: "x".to_string(),
+            data: vec![1.0],
+        };
+        assert_eq!(
+            owned.schema(),
+            vec![ColumnSchema::new("x", ColumnType::Numeric)]
+        );
+        assert!(
+            owned
+                .get_temporal_column("y")
+                .unwrap_err()
+                .to_string()
+                .contains("available columns: x")
+        );
+    }
 }
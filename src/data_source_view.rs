@@ -0,0 +1,299 @@
+// Row subsets of a data source, for faceting, grouping and highlighting.
+// A view holds the selected row indices and reads like any other source: a column is
+// gathered from the underlying source the first time it is read and kept for later reads,
+// so a view can be evaluated, validated and plotted exactly like the original.
+
+use std::cell::OnceCell;
+
+#[cfg(feature = "polars")]
+use polars::prelude::Expr;
+
+use crate::data_groups::DiscreteValue;
+use crate::data_source_self::DataSourceSelf;
+use crate::data_sources::{ColumnSchema, DataError};
+use crate::data_temporal::TemporalColumn;
+
+pub struct RowView<'a, S: DataSourceSelf + ?Sized> {
+    source: &'a S,
+    rows: Vec<usize>,
+    // the source's columns, each numeric one gathered on first read
+    columns: Vec<String>,
+    numeric: Vec<OnceCell<Vec<f64>>>,
+}
+
+// manual impl, the source itself doesn't need to be Clone
+impl<S: DataSourceSelf + ?Sized> Clone for RowView<'_, S> {
+    fn clone(&self) -> Self {
+        RowView {
+            source: self.source,
+            rows: self.rows.clone(),
+            columns: self.columns.clone(),
+            numeric: self.numeric.clone(),
+        }
+    }
+}
+
+impl<'a, S: DataSourceSelf + ?Sized> RowView<'a, S> {
+    // rows are kept in the given order and may repeat
+    pub fn new(source: &'a S, rows: Vec<usize>) -> Result<Self, DataError> {
+        let n_rows = source.n_rows();
+        if let Some(&row) = rows.iter().find(|&&row| row >= n_rows) {
+            return Err(DataError::RowOutOfBounds { row, n_rows });
+        }
+        Ok(Self::with_rows(source, rows))
+    }
+
+    // keeps the rows where the mask is true
+    pub fn from_mask(source: &'a S, mask: &[bool]) -> Result<Self, DataError> {
+        if mask.len() != source.n_rows() {
+            return Err(DataError::LengthMismatch {
+                column: "mask".to_string(),
+                expected: source.n_rows(),
+                actual: mask.len(),
+            });
+        }
+        let rows = mask
+            .iter()
+            .enumerate()
+            .filter_map(|(row, &keep)| keep.then_some(row))
+            .collect();
+        Ok(Self::with_rows(source, rows))
+    }
+
+    // every row of the source
+    pub fn all(source: &'a S) -> Self {
+        Self::with_rows(source, (0..source.n_rows()).collect())
+    }
+
+    fn with_rows(source: &'a S, rows: Vec<usize>) -> Self {
+        let columns = source.column_names();
+        RowView {
+            source,
+            rows,
+            numeric: columns.iter().map(|_| OnceCell::new()).collect(),
+            columns,
+        }
+    }
+
+    pub fn source(&self) -> &'a S {
+        self.source
+    }
+
+    // indices into the underlying source
+    pub fn rows(&self) -> &[usize] {
+        &self.rows
+    }
+
+    // a view of this view, indexed by position within it
+    pub fn subset(&self, rows: &[usize]) -> Result<Self, DataError> {
+        let rows = rows
+            .iter()
+            .map(|&row| {
+                self.rows
+                    .get(row)
+                    .copied()
+                    .ok_or(DataError::RowOutOfBounds {
+                        row,
+                        n_rows: self.rows.len(),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self::with_rows(self.source, rows))
+    }
+
+    // the selected rows where the mask (one entry per row of the view) is true
+    pub fn filter(&self, mask: &[bool]) -> Result<Self, DataError> {
+        if mask.len() != self.rows.len() {
+            return Err(DataError::LengthMismatch {
+                column: "mask".to_string(),
+                expected: self.rows.len(),
+                actual: mask.len(),
+            });
+        }
+        let rows = self
+            .rows
+            .iter()
+            .zip(mask)
+            .filter_map(|(&row, &keep)| keep.then_some(row))
+            .collect();
+        Ok(Self::with_rows(self.source, rows))
+    }
+
+    fn gather<T: Clone>(&self, values: &[T]) -> Vec<T> {
+        self.rows.iter().map(|&row| values[row].clone()).collect()
+    }
+}
+
+impl<S: DataSourceSelf + ?Sized> DataSourceSelf for RowView<'_, S> {
+    fn get_numeric_column(&self, name: &str) -> Result<&[f64], DataError> {
+        let index = self
+            .columns
+            .iter()
+            .position(|column| column == name)
+            .ok_or_else(|| DataError::column_not_found(name, &self.columns))?;
+        let cell = &self.numeric[index];
+        if let Some(values) = cell.get() {
+            return Ok(values);
+        }
+        let values = self.gather(self.source.get_numeric_column(name)?);
+        Ok(cell.get_or_init(|| values))
+    }
+
+    fn n_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn has_columns(&self, name: &str) -> bool {
+        self.source.has_columns(name)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        self.columns.clone()
+    }
+
+    fn schema(&self) -> Vec<ColumnSchema> {
+        self.source.schema()
+    }
+
+    fn get_numeric_column_filled(&self, name: &str) -> Result<Vec<f64>, DataError> {
+        Ok(self.gather(&self.source.get_numeric_column_filled(name)?))
+    }
+
+    fn validity_mask(&self, name: &str) -> Result<Vec<bool>, DataError> {
+        Ok(self.gather(&self.source.validity_mask(name)?))
+    }
+
+    fn get_temporal_column(&self, name: &str) -> Result<TemporalColumn, DataError> {
+        let column = self.source.get_temporal_column(name)?;
+        Ok(TemporalColumn {
+            values: self.gather(&column.values),
+            kind: column.kind,
+        })
+    }
+
+    fn get_discrete_column(&self, name: &str) -> Result<Vec<DiscreteValue>, DataError> {
+        Ok(self.gather(&self.source.get_discrete_column(name)?))
+    }
+
+    fn get_level_codes(&self, name: &str) -> Result<Option<Vec<Option<u32>>>, DataError> {
+        Ok(self
+            .source
+            .get_level_codes(name)?
+            .map(|codes| self.gather(&codes)))
+    }
+
+    // expressions are evaluated over the view's rows only, so a mean() is the view's mean
+    #[cfg(feature = "polars")]
+    fn evaluate_polars_expr(&self, expr: &Expr) -> Result<Vec<f64>, DataError> {
+        self.source.evaluate_polars_expr_on_rows(expr, &self.rows)
+    }
+
+    #[cfg(feature = "polars")]
+    fn evaluate_polars_expr_on_rows(
+        &self,
+        expr: &Expr,
+        rows: &[usize],
+    ) -> Result<Vec<f64>, DataError> {
+        let rows: Vec<usize> = rows.iter().map(|&row| self.rows[row]).collect();
+        self.source.evaluate_polars_expr_on_rows(expr, &rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_expression::ColumnExpr;
+    use crate::data_sources::OwnedColumnSource;
+
+    fn values() -> OwnedColumnSource {
+        OwnedColumnSource {
+            name: "value".to_string(),
+            data: vec![10.0, 20.0, 30.0, 40.0],
+        }
+    }
+
+    #[test]
+    fn test_row_view_from_indices_and_mask() {
+        let source = values();
+
+        let view = RowView::new(&source, vec![3, 1]).unwrap();
+        assert_eq!(view.get_numeric_column("value").unwrap(), vec![40.0, 20.0]);
+        assert_eq!(view.n_rows(), 2);
+        assert!(view.has_columns("value"));
+
+        let masked = RowView::from_mask(&source, &[true, false, true, false]).unwrap();
+        assert_eq!(masked.rows(), &[0, 2]);
+        assert_eq!(
+            (ColumnExpr::column("value") / 10.0)
+                .evaluate(&masked)
+                .unwrap(),
+            vec![1.0, 3.0]
+        );
+
+        // views of views index into the view, not the source
+        let nested = masked.subset(&[1]).unwrap();
+        assert_eq!(nested.get_numeric_column("value").unwrap(), vec![30.0]);
+        let filtered = view.filter(&[false, true]).unwrap();
+        assert_eq!(filtered.rows(), &[1]);
+    }
+
+    #[test]
+    fn test_row_view_reads_like_its_source() {
+        use crate::aesthetics::aes;
+        use crate::plot::Plot;
+
+        let source = values();
+        let view = RowView::new(&source, vec![2, 0, 2]).unwrap();
+        assert_eq!(
+            view.get_discrete_column("value").unwrap(),
+            vec![30.0.into(), 10.0.into(), 30.0.into()]
+        );
+        assert_eq!(view.column_names(), vec!["value"]);
+
+        let evaluated = aes().x("value").y("value").evaluate(&view).unwrap();
+        assert_eq!(evaluated.n_rows, 3);
+        assert!(
+            Plot::new()
+                .data(view)
+                .geom_point(aes().x("value").y("value"))
+                .build()
+                .is_ok()
+        );
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_row_view_aggregates_over_its_rows() {
+        use polars::prelude::{col, df};
+
+        let df = df! { "y" => &[1.0, 2.0, 3.0, 10.0] }.unwrap();
+        let view = RowView::new(&df, vec![0, 2]).unwrap();
+
+        let mean = ColumnExpr::from(col("y").mean());
+        assert_eq!(mean.evaluate(&view).unwrap(), vec![2.0, 2.0]);
+        let centered = ColumnExpr::from(col("y") - col("y").mean());
+        assert_eq!(centered.evaluate(&view).unwrap(), vec![-1.0, 1.0]);
+
+        // a view of a view still only sees its own rows
+        let nested = RowView::new(&view, vec![1]).unwrap();
+        assert_eq!(mean.evaluate(&nested).unwrap(), vec![3.0]);
+    }
+
+    #[test]
+    fn test_row_view_rejects_bad_rows() {
+        let source = values();
+
+        assert!(matches!(
+            RowView::new(&source, vec![0, 4]),
+            Err(DataError::RowOutOfBounds { row: 4, n_rows: 4 })
+        ));
+        assert!(matches!(
+            RowView::from_mask(&source, &[true]),
+            Err(DataError::LengthMismatch { .. })
+        ));
+        assert!(matches!(
+            RowView::all(&source).get_numeric_column("other"),
+            Err(DataError::ColumnNotFound { .. })
+        ));
+    }
+}
@@ -1,17 +1,239 @@
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+#[cfg(feature = "polars")]
 use polars::error::PolarsError;
 
 // error handling
 #[derive(Debug)]
 pub enum DataError {
-    ColumnNotFound(String),
-    TypeMismatch(String),
+    ColumnNotFound {
+        column: String,
+        available: Vec<String>,
+    },
+    TypeMismatch {
+        column: String,
+        expected: String,
+        actual: String,
+    },
     SeriesConvertionFailure(String),
-    PolarsError(String),
+    LengthMismatch {
+        column: String,
+        expected: usize,
+        actual: usize,
+    },
+    MissingValues {
+        column: String,
+        count: usize,
+    },
+    RowOutOfBounds {
+        row: usize,
+        n_rows: usize,
+    },
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    UnsupportedFormat(String),
+    InvalidExpression {
+        expression: String,
+        message: String,
+    },
+    #[cfg(feature = "polars")]
+    PolarsError(PolarsError),
+}
+
+impl DataError {
+    pub fn column_not_found<I, S>(column: &str, available: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        DataError::ColumnNotFound {
+            column: column.to_string(),
+            available: available.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn type_mismatch(column: &str, expected: &str, actual: impl ToString) -> Self {
+        DataError::TypeMismatch {
+            column: column.to_string(),
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        }
+    }
+
+    pub fn io(path: &Path, source: std::io::Error) -> Self {
+        DataError::Io {
+            path: path.display().to_string(),
+            source,
+        }
+    }
+
+    // close matches among the available columns for a ColumnNotFound error, best first
+    pub fn suggestions(&self) -> Vec<&str> {
+        let DataError::ColumnNotFound { column, available } = self else {
+            return Vec::new();
+        };
+
+        let wanted = column.to_lowercase();
+        let max_distance = (wanted.chars().count() / 3).max(1);
+        let mut candidates: Vec<(usize, &str)> = available
+            .iter()
+            .filter_map(|name| {
+                let distance = edit_distance(&wanted, &name.to_lowercase());
+                (distance <= max_distance).then_some((distance, name.as_str()))
+            })
+            .collect();
+        candidates.sort_by_key(|&(distance, _)| distance);
+        candidates.into_iter().map(|(_, name)| name).collect()
+    }
 }
 
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::ColumnNotFound { column, available } => {
+                write!(f, "Column '{}' not found", column)?;
+                let suggestions = self.suggestions();
+                if !suggestions.is_empty() {
+                    write!(f, "; did you mean '{}'?", suggestions.join("' or '"))?;
+                }
+                if !available.is_empty() {
+                    write!(f, " (available columns: {})", available.join(", "))?;
+                }
+                Ok(())
+            }
+            DataError::TypeMismatch {
+                column,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Column '{}' has type {}, expected {}",
+                column, actual, expected
+            ),
+            DataError::SeriesConvertionFailure(message) => {
+                write!(f, "Series conversion failed: {}", message)
+            }
+            DataError::LengthMismatch {
+                column,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Column '{}' has {} rows, expected {}",
+                column, actual, expected
+            ),
+            DataError::MissingValues { column, count } => {
+                write!(f, "Column '{}' has {} missing values", column, count)
+            }
+            DataError::RowOutOfBounds { row, n_rows } => {
+                write!(f, "Row {} is out of bounds for {} rows", row, n_rows)
+            }
+            DataError::Io { path, source } => write!(f, "Could not read '{}': {}", path, source),
+            DataError::UnsupportedFormat(path) => {
+                write!(f, "Unsupported file format for '{}'", path)
+            }
+            DataError::InvalidExpression {
+                expression,
+                message,
+            } => write!(f, "Invalid expression '{}': {}", expression, message),
+            #[cfg(feature = "polars")]
+            DataError::PolarsError(err) => write!(f, "Polars error: {}", err),
+        }
+    }
+}
+
+impl Error for DataError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DataError::Io { source, .. } => Some(source),
+            #[cfg(feature = "polars")]
+            DataError::PolarsError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "polars")]
 impl From<PolarsError> for DataError {
     fn from(err: PolarsError) -> Self {
-        DataError::PolarsError(err.to_string())
+        DataError::PolarsError(err)
+    }
+}
+
+// Levenshtein distance, used for "did you mean" suggestions
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// position of a column in a matrix-like source: by name first, then by index ("0", "1", ...)
+#[cfg(any(feature = "ndarray", feature = "nalgebra"))]
+pub(crate) fn column_position(names: &[String], name: &str) -> Option<usize> {
+    names
+        .iter()
+        .position(|column_name| column_name == name)
+        .or_else(|| {
+            name.parse::<usize>()
+                .ok()
+                .filter(|&index| index < names.len())
+        })
+}
+
+// logical column types, as far as plotting cares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Numeric,
+    // strings, categoricals and anything else that is only compared for equality
+    Categorical,
+    // dates, datetimes and durations
+    Temporal,
+    Boolean,
+}
+
+impl ColumnType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColumnType::Numeric => "numeric",
+            ColumnType::Categorical => "categorical",
+            ColumnType::Temporal => "temporal",
+            ColumnType::Boolean => "boolean",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSchema {
+    pub name: String,
+    pub column_type: ColumnType,
+}
+
+impl ColumnSchema {
+    pub fn new(name: impl Into<String>, column_type: ColumnType) -> Self {
+        ColumnSchema {
+            name: name.into(),
+            column_type,
+        }
+    }
+
+    // schema of a source that only holds f64 columns
+    pub fn all_numeric(names: Vec<String>) -> Vec<Self> {
+        names
+            .into_iter()
+            .map(|name| ColumnSchema::new(name, ColumnType::Numeric))
+            .collect()
     }
 }
 
@@ -25,3 +247,39 @@ pub struct BorrowedColumnSource<'a> {
     pub name: String,
     pub data: &'a [f64],
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_not_found_suggests_close_matches() {
+        let err = DataError::column_not_found("hieght", ["age", "height", "weight"]);
+        assert_eq!(err.suggestions(), vec!["height"]);
+        assert_eq!(
+            err.to_string(),
+            "Column 'hieght' not found; did you mean 'height'? \
+             (available columns: age, height, weight)"
+        );
+
+        // nothing close enough: no suggestion, but the alternatives are still listed
+        let err = DataError::column_not_found("price", ["age"]);
+        assert!(err.suggestions().is_empty());
+        assert_eq!(
+            err.to_string(),
+            "Column 'price' not found (available columns: age)"
+        );
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_polars_error_is_kept_as_source() {
+        let err = DataError::from(PolarsError::ComputeError("boom".into()));
+        assert!(err.source().is_some());
+        assert!(err.to_string().contains("boom"));
+
+        let err = DataError::type_mismatch("x", "f64", "str");
+        assert!(err.source().is_none());
+        assert_eq!(err.to_string(), "Column 'x' has type str, expected f64");
+    }
+}
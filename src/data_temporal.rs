@@ -0,0 +1,102 @@
+// Temporal columns keep their raw integer representation plus the unit it is counted in,
+// so a time scale can later place ticks on calendar boundaries instead of raw epoch numbers.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+}
+
+impl TimeUnit {
+    pub fn ticks_per_second(&self) -> i64 {
+        match self {
+            TimeUnit::Nanoseconds => 1_000_000_000,
+            TimeUnit::Microseconds => 1_000_000,
+            TimeUnit::Milliseconds => 1_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemporalKind {
+    // days since 1970-01-01
+    Date,
+    // ticks since the unix epoch (UTC); the time zone is only used for display
+    Datetime {
+        unit: TimeUnit,
+        time_zone: Option<String>,
+    },
+    // elapsed ticks
+    Duration {
+        unit: TimeUnit,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemporalColumn {
+    pub kind: TemporalKind,
+    pub values: Vec<Option<i64>>,
+}
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+// what a temporal getter expects, for type mismatch errors
+pub const TEMPORAL_TYPES: &str = "date, datetime or duration";
+
+impl TemporalColumn {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    // seconds since the unix epoch (elapsed seconds for durations), missing values as NaN
+    pub fn to_seconds(&self) -> Vec<f64> {
+        let seconds_per_value = match &self.kind {
+            TemporalKind::Date => SECONDS_PER_DAY as f64,
+            TemporalKind::Datetime { unit, .. } | TemporalKind::Duration { unit } => {
+                1.0 / unit.ticks_per_second() as f64
+            }
+        };
+        self.values
+            .iter()
+            .map(|value| value.map_or(f64::NAN, |ticks| ticks as f64 * seconds_per_value))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_seconds_respects_units() {
+        let dates = TemporalColumn {
+            kind: TemporalKind::Date,
+            values: vec![Some(0), Some(1), None],
+        };
+        let seconds = dates.to_seconds();
+        assert_eq!(&seconds[..2], &[0.0, 86_400.0]);
+        assert!(seconds[2].is_nan());
+
+        let datetimes = TemporalColumn {
+            kind: TemporalKind::Datetime {
+                unit: TimeUnit::Milliseconds,
+                time_zone: Some("Europe/London".to_string()),
+            },
+            values: vec![Some(1_500)],
+        };
+        assert_eq!(datetimes.to_seconds(), vec![1.5]);
+
+        let durations = TemporalColumn {
+            kind: TemporalKind::Duration {
+                unit: TimeUnit::Microseconds,
+            },
+            values: vec![Some(2_000_000)],
+        };
+        assert_eq!(durations.to_seconds(), vec![2.0]);
+    }
+}
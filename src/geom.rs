@@ -0,0 +1,445 @@
+// Geoms: how a layer's rows are drawn. A geom receives the rows with every aesthetic
+// already resolved (positions scaled to the panel, colours and sizes in visual units)
+// and turns them into draw commands.
+
+use std::f64::consts::PI;
+
+use crate::aesthetics::{Aesthetic, AestheticMapping, aes};
+use crate::coord::{CoordSystem, Rect};
+use crate::primitives::{Color, DrawCommand, Point, Stroke};
+
+// point markers, named as in scale_discrete::SHAPES
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Circle,
+    Triangle,
+    Square,
+    Plus,
+    Cross,
+    Diamond,
+}
+
+impl Shape {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "circle" => Some(Shape::Circle),
+            "triangle" => Some(Shape::Triangle),
+            "square" => Some(Shape::Square),
+            "plus" => Some(Shape::Plus),
+            "cross" => Some(Shape::Cross),
+            "diamond" => Some(Shape::Diamond),
+            _ => None,
+        }
+    }
+}
+
+// line dash patterns, named as in scale_discrete::LINETYPES
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linetype {
+    Solid,
+    Dashed,
+    Dotted,
+    DotDash,
+    LongDash,
+    TwoDash,
+}
+
+impl Linetype {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "solid" => Some(Linetype::Solid),
+            "dashed" => Some(Linetype::Dashed),
+            "dotted" => Some(Linetype::Dotted),
+            "dotdash" => Some(Linetype::DotDash),
+            "longdash" => Some(Linetype::LongDash),
+            "twodash" => Some(Linetype::TwoDash),
+            _ => None,
+        }
+    }
+
+    // alternating on and off lengths in line widths, as ggplot2's; empty when solid
+    pub fn dashes(&self) -> &'static [f64] {
+        match self {
+            Linetype::Solid => &[],
+            Linetype::Dashed => &[4.0, 4.0],
+            Linetype::Dotted => &[1.0, 3.0],
+            Linetype::DotDash => &[1.0, 3.0, 4.0, 3.0],
+            Linetype::LongDash => &[7.0, 3.0],
+            Linetype::TwoDash => &[2.0, 2.0, 6.0, 2.0],
+        }
+    }
+}
+
+// one entry per row; positions run from 0 to 1 across the panel, NaN when missing
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProcessedData {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub color: Vec<Color>,
+    pub fill: Vec<Color>,
+    // point radius in pixels
+    pub size: Vec<f64>,
+    pub alpha: Vec<f64>,
+    // line width in pixels
+    pub linewidth: Vec<f64>,
+    // None for rows whose value has no shape or line type, which aren't drawn
+    pub shape: Vec<Option<Shape>>,
+    pub linetype: Vec<Option<Linetype>>,
+    // rows with the same group are drawn together (e.g. as one line)
+    pub group: Vec<usize>,
+}
+
+impl ProcessedData {
+    pub fn n_rows(&self) -> usize {
+        self.x.len()
+    }
+
+    fn has_position(&self, row: usize) -> bool {
+        self.x[row].is_finite() && self.y[row].is_finite()
+    }
+}
+
+pub trait Geom {
+    // used to name the layer in diagnostics
+    fn name(&self) -> &'static str;
+
+    fn required_aes(&self) -> Vec<Aesthetic>;
+
+    fn optional_aes(&self) -> Vec<Aesthetic>;
+
+    // fixed values replacing the global defaults for this geom
+    fn default_aes(&self) -> AestheticMapping {
+        aes()
+    }
+
+    fn draw(&self, data: &ProcessedData, coord: &dyn CoordSystem, panel: &Rect)
+    -> Vec<DrawCommand>;
+}
+
+// scatter plots: one filled marker per row
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GeomPoint;
+
+impl Geom for GeomPoint {
+    fn name(&self) -> &'static str {
+        "point"
+    }
+
+    fn required_aes(&self) -> Vec<Aesthetic> {
+        vec![Aesthetic::X, Aesthetic::Y]
+    }
+
+    fn optional_aes(&self) -> Vec<Aesthetic> {
+        vec![
+            Aesthetic::Color,
+            Aesthetic::Size,
+            Aesthetic::Alpha,
+            Aesthetic::Shape,
+            Aesthetic::Group,
+        ]
+    }
+
+    fn draw(
+        &self,
+        data: &ProcessedData,
+        coord: &dyn CoordSystem,
+        panel: &Rect,
+    ) -> Vec<DrawCommand> {
+        (0..data.n_rows())
+            .filter(|&row| data.has_position(row))
+            .flat_map(|row| {
+                let Some(shape) = data.shape[row] else {
+                    return Vec::new();
+                };
+                marker(
+                    shape,
+                    coord.transform(data.x[row], data.y[row], panel),
+                    data.size[row],
+                    data.color[row].with_alpha(data.alpha[row]),
+                )
+            })
+            .collect()
+    }
+}
+
+// a marker of about the same size as a circle of this radius
+fn marker(shape: Shape, center: Point, radius: f64, color: Color) -> Vec<DrawCommand> {
+    // corners at these angles (clockwise from the top, as y points down) and distance
+    let polygon = |angles: &[f64], distance: f64| DrawCommand::Polygon {
+        points: angles
+            .iter()
+            .map(|angle: &f64| {
+                let angle = angle.to_radians() - PI / 2.0;
+                Point {
+                    x: center.x + distance * angle.cos(),
+                    y: center.y + distance * angle.sin(),
+                }
+            })
+            .collect(),
+        fill: Some(color),
+        stroke: None,
+    };
+    // strokes through the centre at these angles
+    let strokes = |angles: [f64; 2]| {
+        angles
+            .iter()
+            .map(|angle: &f64| {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                DrawCommand::Line {
+                    start: Point {
+                        x: center.x - radius * cos,
+                        y: center.y - radius * sin,
+                    },
+                    end: Point {
+                        x: center.x + radius * cos,
+                        y: center.y + radius * sin,
+                    },
+                    stroke: Some(Stroke {
+                        color: Some(color),
+                        width: (radius / 3.0).max(1.0),
+                    }),
+                }
+            })
+            .collect()
+    };
+    match shape {
+        Shape::Circle => vec![DrawCommand::Circle {
+            position: center,
+            radius,
+            fill: Some(color),
+            stroke: None,
+        }],
+        Shape::Triangle => vec![polygon(&[0.0, 120.0, 240.0], radius * 1.3)],
+        Shape::Square => vec![polygon(&[45.0, 135.0, 225.0, 315.0], radius * 1.25)],
+        Shape::Diamond => vec![polygon(&[0.0, 90.0, 180.0, 270.0], radius * 1.25)],
+        Shape::Plus => strokes([0.0, 90.0]),
+        Shape::Cross => strokes([45.0, 135.0]),
+    }
+}
+
+// one line per group, connecting the rows in order of x; missing rows break the line.
+// Dashes carry on from one segment to the next, so a dense line still shows them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GeomLine;
+
+impl Geom for GeomLine {
+    fn name(&self) -> &'static str {
+        "line"
+    }
+
+    fn required_aes(&self) -> Vec<Aesthetic> {
+        vec![Aesthetic::X, Aesthetic::Y]
+    }
+
+    fn optional_aes(&self) -> Vec<Aesthetic> {
+        vec![
+            Aesthetic::Color,
+            Aesthetic::Alpha,
+            Aesthetic::Linetype,
+            Aesthetic::Linewidth,
+            Aesthetic::Group,
+        ]
+    }
+
+    fn draw(
+        &self,
+        data: &ProcessedData,
+        coord: &dyn CoordSystem,
+        panel: &Rect,
+    ) -> Vec<DrawCommand> {
+        let mut rows: Vec<usize> = (0..data.n_rows()).collect();
+        rows.sort_by(|&a, &b| {
+            data.group[a]
+                .cmp(&data.group[b])
+                .then(data.x[a].total_cmp(&data.x[b]))
+        });
+
+        let mut commands = Vec::new();
+        // how far into its dash pattern the line is, None where it was broken
+        let mut phase = None;
+        for pair in rows.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            if data.group[start] != data.group[end]
+                || !data.has_position(start)
+                || !data.has_position(end)
+            {
+                phase = None;
+                continue;
+            }
+            // each segment takes the style of the row it starts from
+            let Some(linetype) = data.linetype[start] else {
+                phase = None;
+                continue;
+            };
+            let stroke = Some(Stroke {
+                color: Some(data.color[start].with_alpha(data.alpha[start])),
+                width: data.linewidth[start],
+            });
+            let from = coord.transform(data.x[start], data.y[start], panel);
+            let to = coord.transform(data.x[end], data.y[end], panel);
+            // dash lengths scale with the line, but no shorter than a pixel
+            let unit = data.linewidth[start].max(1.0);
+            let pattern: Vec<f64> = linetype.dashes().iter().map(|dash| dash * unit).collect();
+            let (pieces, end_phase) = dash_segment(from, to, &pattern, phase.unwrap_or(0.0));
+            phase = Some(end_phase);
+            commands.extend(pieces.into_iter().map(|(start, end)| DrawCommand::Line {
+                start,
+                end,
+                stroke,
+            }));
+        }
+        commands
+    }
+}
+
+// the drawn pieces of the segment from start to end under a dash pattern (alternating
+// on and off lengths, solid when empty), starting `phase` pixels into the pattern;
+// also returns how far into the pattern the segment ends
+fn dash_segment(
+    start: Point,
+    end: Point,
+    pattern: &[f64],
+    phase: f64,
+) -> (Vec<(Point, Point)>, f64) {
+    let period: f64 = pattern.iter().sum();
+    let length = (end.x - start.x).hypot(end.y - start.y);
+    if period <= 0.0 || !length.is_finite() {
+        return (vec![(start, end)], 0.0);
+    }
+    let at = |distance: f64| Point {
+        x: start.x + (end.x - start.x) * distance / length,
+        y: start.y + (end.y - start.y) * distance / length,
+    };
+
+    // the dash the phase falls in, and how far into it
+    let mut index = 0;
+    let mut offset = phase % period;
+    while offset >= pattern[index] {
+        offset -= pattern[index];
+        index = (index + 1) % pattern.len();
+    }
+
+    let mut pieces = Vec::new();
+    let mut position = 0.0;
+    while position < length {
+        let step = (pattern[index] - offset).min(length - position);
+        if index % 2 == 0 && step > 0.0 {
+            pieces.push((at(position), at(position + step)));
+        }
+        position += step;
+        offset += step;
+        if offset >= pattern[index] {
+            offset = 0.0;
+            index = (index + 1) % pattern.len();
+        }
+    }
+    (pieces, (phase + length) % period)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::CoordCartesian;
+
+    fn processed(x: Vec<f64>, y: Vec<f64>, group: Vec<usize>) -> ProcessedData {
+        let n = x.len();
+        ProcessedData {
+            x,
+            y,
+            color: vec![Color::default(); n],
+            fill: vec![Color::default(); n],
+            size: vec![2.0; n],
+            alpha: vec![1.0; n],
+            linewidth: vec![1.0; n],
+            shape: vec![Some(Shape::Circle); n],
+            linetype: vec![Some(Linetype::Solid); n],
+            group,
+        }
+    }
+
+    const PANEL: Rect = Rect {
+        x: 0.0,
+        y: 0.0,
+        width: 100.0,
+        height: 100.0,
+    };
+
+    #[test]
+    fn test_point_skips_rows_without_position() {
+        let data = processed(vec![0.0, f64::NAN, 1.0], vec![0.0, 0.5, 1.0], vec![0; 3]);
+        let commands = GeomPoint.draw(&data, &CoordCartesian, &PANEL);
+
+        assert_eq!(commands.len(), 2);
+        assert!(matches!(
+            commands[1],
+            DrawCommand::Circle {
+                position: Point { x: 100.0, y: 0.0 },
+                radius: 2.0,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_line_connects_rows_in_x_order_within_groups() {
+        let data = processed(
+            vec![1.0, 0.0, 0.5, 0.0, 1.0],
+            vec![0.0; 5],
+            vec![0, 0, 0, 1, 1],
+        );
+        let commands = GeomLine.draw(&data, &CoordCartesian, &PANEL);
+
+        // two segments in the first group, one in the second
+        assert_eq!(commands.len(), 3);
+        assert!(matches!(
+            commands[0],
+            DrawCommand::Line {
+                start: Point { x: 0.0, .. },
+                end: Point { x: 50.0, .. },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_point_shapes_and_dashed_lines() {
+        let mut data = processed(vec![0.0, 0.5, 1.0], vec![0.5; 3], vec![0; 3]);
+        data.shape = vec![Some(Shape::Square), Some(Shape::Plus), None];
+        let commands = GeomPoint.draw(&data, &CoordCartesian, &PANEL);
+        // a square, the two strokes of a plus, and nothing for the row without a shape
+        assert_eq!(commands.len(), 3);
+        assert!(matches!(&commands[0], DrawCommand::Polygon { points, .. } if points.len() == 4));
+        assert!(matches!(commands[1], DrawCommand::Line { .. }));
+
+        // 100 pixels of 4 on, 4 off, carried across the joint at 50 pixels
+        data.linetype = vec![Some(Linetype::Dashed); 3];
+        let commands = GeomLine.draw(&data, &CoordCartesian, &PANEL);
+        assert_eq!(commands.len(), 14);
+        assert!(matches!(
+            commands[6],
+            DrawCommand::Line {
+                start: Point { x: 48.0, .. },
+                end: Point { x: 50.0, .. },
+                ..
+            }
+        ));
+        assert!(matches!(
+            commands[7],
+            DrawCommand::Line {
+                start: Point { x: 50.0, .. },
+                end: Point { x: 52.0, .. },
+                ..
+            }
+        ));
+
+        let (pieces, phase) = dash_segment(
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 10.0, y: 0.0 },
+            &[4.0, 4.0],
+            2.0,
+        );
+        assert_eq!(pieces.len(), 2);
+        assert_eq!((pieces[0].1.x, pieces[1].0.x), (2.0, 6.0));
+        assert_eq!(phase, 4.0);
+    }
+}
@@ -0,0 +1,61 @@
+// A layer is one geom drawn with its own mapping, and optionally its own data.
+// Whatever the layer leaves unspecified is taken from the plot.
+
+use crate::aesthetics::{AestheticMapping, aes};
+#[cfg(feature = "polars")]
+use crate::data_source_lazy::LazyFrameSource;
+use crate::data_source_self::DataSourceSelf;
+use crate::geom::Geom;
+
+// a layer's or plot's data; a lazy query is collected when the plot is built, and then
+// only the columns the layer's mapping reads
+pub enum LayerData<'a> {
+    Source(Box<dyn DataSourceSelf + 'a>),
+    #[cfg(feature = "polars")]
+    Lazy(Box<LazyFrameSource>),
+}
+
+pub struct Layer<'a> {
+    geom: Box<dyn Geom>,
+    mapping: AestheticMapping,
+    data: Option<LayerData<'a>>,
+}
+
+impl<'a> Layer<'a> {
+    pub fn new(geom: impl Geom + 'static) -> Self {
+        Layer {
+            geom: Box::new(geom),
+            mapping: aes(),
+            data: None,
+        }
+    }
+
+    pub fn mapping(mut self, mapping: AestheticMapping) -> Self {
+        self.mapping = mapping;
+        self
+    }
+
+    // data for this layer only, instead of the plot's; may borrow, e.g. &df or a RowView
+    pub fn data(mut self, data: impl DataSourceSelf + 'a) -> Self {
+        self.data = Some(LayerData::Source(Box::new(data)));
+        self
+    }
+
+    #[cfg(feature = "polars")]
+    pub fn lazy_data(mut self, data: impl Into<LazyFrameSource>) -> Self {
+        self.data = Some(LayerData::Lazy(Box::new(data.into())));
+        self
+    }
+
+    pub fn geom(&self) -> &dyn Geom {
+        self.geom.as_ref()
+    }
+
+    pub fn get_mapping(&self) -> &AestheticMapping {
+        &self.mapping
+    }
+
+    pub fn get_data(&self) -> Option<&LayerData<'a>> {
+        self.data.as_ref()
+    }
+}
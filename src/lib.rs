@@ -0,0 +1,44 @@
+// lets code generated by #[derive(DataSource)] inside this crate (e.g. in tests) refer to it as `::gracex`
+extern crate self as gracex;
+
+pub mod aesthetic_validation;
+pub mod aesthetics;
+pub mod coord;
+pub mod data_expression;
+pub mod data_groups;
+#[cfg(feature = "polars")]
+pub mod data_loaders;
+pub mod data_missing;
+#[cfg(feature = "arrow")]
+pub mod data_source_arrow;
+pub mod data_source_borrowed;
+pub mod data_source_chunked;
+pub mod data_source_grid;
+#[cfg(feature = "polars")]
+pub mod data_source_lazy;
+#[cfg(feature = "nalgebra")]
+pub mod data_source_nalgebra;
+#[cfg(feature = "ndarray")]
+pub mod data_source_ndarray;
+pub mod data_source_owned;
+pub mod data_source_records;
+pub mod data_source_self;
+pub mod data_source_view;
+pub mod data_sources;
+pub mod data_temporal;
+pub mod geom;
+pub mod layer;
+pub mod plot;
+pub mod prelude;
+pub mod primitives;
+pub mod renderer;
+pub mod scale_breaks;
+pub mod scale_color;
+pub mod scale_discrete;
+pub mod scale_labels;
+pub mod scale_manual;
+pub mod scale_range;
+pub mod scale_time;
+pub mod scale_transform;
+pub mod scales;
+pub mod theme;
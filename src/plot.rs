@@ -0,0 +1,1651 @@
+// The user-facing entry point: a Plot collects data, layers, coordinate system, theme
+// and labels, and `build` turns them into the draw commands a renderer consumes.
+//
+// Positions go through the x and y scales, colours through colour scales, size, alpha
+// and line width through range scales and shapes and line types through discrete scales,
+// all trained on every layer. Manual and identity
+// scales take colours and numbers from the user or straight from the data instead.
+
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use chrono_tz::Tz;
+
+use crate::aesthetic_validation::{AestheticError, AestheticWarning, validate_mapping};
+use crate::aesthetics::{
+    AesValue, Aesthetic, AestheticMapping, AestheticValue, AestheticValues, EvaluatedAesthetics,
+    aes,
+};
+use crate::coord::{CoordCartesian, CoordSystem, Rect};
+use crate::data_expression::ColumnExpr;
+use crate::data_groups::{DiscreteValue, group_rows};
+use crate::data_missing::MissingPolicy;
+#[cfg(feature = "polars")]
+use crate::data_source_lazy::LazyFrameSource;
+use crate::data_source_self::DataSourceSelf;
+use crate::data_sources::{ColumnType, DataError};
+use crate::data_temporal::TemporalKind;
+use crate::geom::{Geom, GeomLine, GeomPoint, Linetype, ProcessedData, Shape};
+use crate::layer::{Layer, LayerData};
+use crate::primitives::{Color, DrawCommand, Point, Stroke};
+use crate::renderer::{PngRenderer, Renderer};
+use crate::scale_color::{ColorScale, GradientScale};
+use crate::scale_discrete::{DiscreteScale, NameScale};
+use crate::scale_range::{NumberScale, RangeScale};
+use crate::scale_time::TimeScale;
+use crate::scales::{ContinuousScale, Scale};
+use crate::theme::Theme;
+
+#[derive(Debug)]
+pub enum PlotError {
+    // neither the layer nor the plot has data
+    NoData {
+        layer: String,
+    },
+    Data(DataError),
+    Aesthetic(AestheticError),
+    // a mapping this plot can't draw yet, e.g. a categorical position
+    UnsupportedMapping {
+        layer: String,
+        aesthetic: Aesthetic,
+        message: String,
+    },
+    UnsupportedFormat(String),
+    Render(std::io::Error),
+}
+
+impl fmt::Display for PlotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlotError::NoData { layer } => {
+                write!(
+                    f,
+                    "Layer '{}' has no data and the plot has none to inherit",
+                    layer
+                )
+            }
+            PlotError::Data(err) => write!(f, "{}", err),
+            PlotError::Aesthetic(err) => write!(f, "{}", err),
+            PlotError::UnsupportedMapping {
+                layer,
+                aesthetic,
+                message,
+            } => write!(
+                f,
+                "Layer '{}', aesthetic '{}': {}",
+                layer, aesthetic, message
+            ),
+            PlotError::UnsupportedFormat(extension) => {
+                write!(f, "Unsupported output format '{}'", extension)
+            }
+            PlotError::Render(err) => write!(f, "Rendering failed: {}", err),
+        }
+    }
+}
+
+impl Error for PlotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PlotError::Data(err) => Some(err),
+            PlotError::Aesthetic(err) => Some(err),
+            PlotError::Render(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<DataError> for PlotError {
+    fn from(err: DataError) -> Self {
+        PlotError::Data(err)
+    }
+}
+
+impl From<AestheticError> for PlotError {
+    fn from(err: AestheticError) -> Self {
+        PlotError::Aesthetic(err)
+    }
+}
+
+impl From<std::io::Error> for PlotError {
+    fn from(err: std::io::Error) -> Self {
+        PlotError::Render(err)
+    }
+}
+
+// something the plot could still be drawn despite; build_with_warnings and save return
+// them for the caller to show or ignore
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlotWarning {
+    Aesthetic(AestheticWarning),
+    // a datetime column's time zone isn't known, so times are shown in UTC
+    UnknownTimeZone(String),
+    // values outside the domain of a scale's transformation (e.g. zero on a log scale),
+    // which aren't drawn
+    Untransformable {
+        layer: String,
+        aesthetic: Aesthetic,
+        count: usize,
+    },
+    // rows dropped under MissingPolicy::DropWithWarning
+    MissingValues {
+        layer: String,
+        count: usize,
+    },
+}
+
+impl fmt::Display for PlotWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlotWarning::Aesthetic(warning) => write!(f, "{}", warning),
+            PlotWarning::UnknownTimeZone(zone) => {
+                write!(f, "Unknown time zone '{}', showing times in UTC", zone)
+            }
+            PlotWarning::Untransformable {
+                layer,
+                aesthetic,
+                count,
+            } => write!(
+                f,
+                "Layer '{}', aesthetic '{}': {} values are outside the domain of the scale's transformation and were removed",
+                layer, aesthetic, count
+            ),
+            PlotWarning::MissingValues { layer, count } => write!(
+                f,
+                "Layer '{}': removed {} rows containing missing values",
+                layer, count
+            ),
+        }
+    }
+}
+
+impl From<AestheticWarning> for PlotWarning {
+    fn from(warning: AestheticWarning) -> Self {
+        PlotWarning::Aesthetic(warning)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Labels {
+    pub title: Option<String>,
+    pub x: Option<String>,
+    pub y: Option<String>,
+}
+
+pub struct Plot<'a> {
+    data: Option<LayerData<'a>>,
+    mapping: AestheticMapping,
+    layers: Vec<Layer<'a>>,
+    coord: Box<dyn CoordSystem>,
+    theme: Theme,
+    labels: Labels,
+    // None picks a scale from the data: time scales for dates and datetimes,
+    // continuous ones otherwise
+    x_scale: Option<Box<dyn Scale>>,
+    y_scale: Option<Box<dyn Scale>>,
+    // None picks a gradient for continuous values, a discrete scale otherwise
+    color_scale: Option<ColorScale>,
+    fill_scale: Option<ColorScale>,
+    size_scale: NumberScale,
+    alpha_scale: NumberScale,
+    linewidth_scale: NumberScale,
+    shape_scale: NameScale,
+    linetype_scale: NameScale,
+    missing_policy: MissingPolicy,
+    width: u32,
+    height: u32,
+}
+
+impl Default for Plot<'_> {
+    fn default() -> Self {
+        Plot::new()
+    }
+}
+
+impl<'a> Plot<'a> {
+    pub fn new() -> Self {
+        Plot {
+            data: None,
+            mapping: aes(),
+            layers: Vec::new(),
+            coord: Box::new(CoordCartesian),
+            theme: Theme::default(),
+            labels: Labels::default(),
+            x_scale: None,
+            y_scale: None,
+            color_scale: None,
+            fill_scale: None,
+            size_scale: RangeScale::size().into(),
+            alpha_scale: RangeScale::alpha().into(),
+            linewidth_scale: RangeScale::linewidth().into(),
+            shape_scale: DiscreteScale::new().into(),
+            linetype_scale: DiscreteScale::new().into(),
+            missing_policy: MissingPolicy::default(),
+            width: 600,
+            height: 400,
+        }
+    }
+
+    // data shared by every layer that doesn't bring its own; may borrow, e.g. &df
+    pub fn data(mut self, data: impl DataSourceSelf + 'a) -> Self {
+        self.data = Some(LayerData::Source(Box::new(data)));
+        self
+    }
+
+    // a lazy query (e.g. LazyFrameSource::scan_parquet), collected on build with only the
+    // columns each layer's mapping reads, so polars can push the projection into the scan
+    #[cfg(feature = "polars")]
+    pub fn lazy_data(mut self, data: impl Into<LazyFrameSource>) -> Self {
+        self.data = Some(LayerData::Lazy(Box::new(data.into())));
+        self
+    }
+
+    // mapping inherited by every layer
+    pub fn mapping(mut self, mapping: AestheticMapping) -> Self {
+        self.mapping = mapping;
+        self
+    }
+
+    pub fn layer(mut self, layer: Layer<'a>) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    pub fn geom_point(self, mapping: AestheticMapping) -> Self {
+        self.layer(Layer::new(GeomPoint).mapping(mapping))
+    }
+
+    pub fn geom_line(self, mapping: AestheticMapping) -> Self {
+        self.layer(Layer::new(GeomLine).mapping(mapping))
+    }
+
+    // position scales map onto the panel, so their output range should stay 0..1
+    pub fn x_scale(mut self, scale: impl Scale + 'static) -> Self {
+        self.x_scale = Some(Box::new(scale));
+        self
+    }
+
+    pub fn y_scale(mut self, scale: impl Scale + 'static) -> Self {
+        self.y_scale = Some(Box::new(scale));
+        self
+    }
+
+    // a GradientScale, DiscreteScale, ManualScale or IdentityScale
+    pub fn color_scale(mut self, scale: impl Into<ColorScale>) -> Self {
+        self.color_scale = Some(scale.into());
+        self
+    }
+
+    pub fn fill_scale(mut self, scale: impl Into<ColorScale>) -> Self {
+        self.fill_scale = Some(scale.into());
+        self
+    }
+
+    // a RangeScale, ManualScale or IdentityScale; range scales for sizes change in area
+    // unless made with RangeScale::radius()
+    pub fn size_scale(mut self, scale: impl Into<NumberScale>) -> Self {
+        self.size_scale = scale.into();
+        self
+    }
+
+    pub fn alpha_scale(mut self, scale: impl Into<NumberScale>) -> Self {
+        self.alpha_scale = scale.into();
+        self
+    }
+
+    pub fn linewidth_scale(mut self, scale: impl Into<NumberScale>) -> Self {
+        self.linewidth_scale = scale.into();
+        self
+    }
+
+    // a DiscreteScale picking from its shapes (DiscreteScale::shapes) in level order,
+    // a ManualScale of shape names or an IdentityScale taking the names from the data
+    pub fn shape_scale(mut self, scale: impl Into<NameScale>) -> Self {
+        self.shape_scale = scale.into();
+        self
+    }
+
+    pub fn linetype_scale(mut self, scale: impl Into<NameScale>) -> Self {
+        self.linetype_scale = scale.into();
+        self
+    }
+
+    // what happens to rows missing a value in any aesthetic a layer maps; by default
+    // they are dropped with a warning
+    pub fn missing_policy(mut self, policy: MissingPolicy) -> Self {
+        self.missing_policy = policy;
+        self
+    }
+
+    // shorthand for a continuous x scale with these limits
+    pub fn xlim(self, min: f64, max: f64) -> Self {
+        self.x_scale(ContinuousScale::new().limits(min, max))
+    }
+
+    pub fn ylim(self, min: f64, max: f64) -> Self {
+        self.y_scale(ContinuousScale::new().limits(min, max))
+    }
+
+    pub fn coord(mut self, coord: impl CoordSystem + 'static) -> Self {
+        self.coord = Box::new(coord);
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.labels.title = Some(title.into());
+        self
+    }
+
+    pub fn x_label(mut self, label: impl Into<String>) -> Self {
+        self.labels.x = Some(label.into());
+        self
+    }
+
+    pub fn y_label(mut self, label: impl Into<String>) -> Self {
+        self.labels.y = Some(label.into());
+        self
+    }
+
+    // output size in pixels
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn build(&self) -> Result<Vec<DrawCommand>, PlotError> {
+        self.build_with_warnings().map(|(commands, _)| commands)
+    }
+
+    // the draw commands and whatever was worth a warning along the way
+    pub fn build_with_warnings(&self) -> Result<(Vec<DrawCommand>, Vec<PlotWarning>), PlotError> {
+        let mut warnings = Vec::new();
+        let layers = self
+            .layers
+            .iter()
+            .map(|layer| self.prepare_layer(layer, &mut warnings))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // trained afresh on every build
+        let (x_scale, x) = train_position_scale(
+            self.x_scale.as_deref(),
+            Aesthetic::X,
+            &layers,
+            &mut warnings,
+        )?;
+        let (y_scale, y) = train_position_scale(
+            self.y_scale.as_deref(),
+            Aesthetic::Y,
+            &layers,
+            &mut warnings,
+        )?;
+        let (x_scale, y_scale) = (x_scale.as_ref(), y_scale.as_ref());
+        let color_scale = train_color_scale(self.color_scale.as_ref(), Aesthetic::Color, &layers);
+        let fill_scale = train_color_scale(self.fill_scale.as_ref(), Aesthetic::Fill, &layers);
+        let size_scale = train_number_scale(&self.size_scale, Aesthetic::Size, &layers);
+        let alpha_scale = train_number_scale(&self.alpha_scale, Aesthetic::Alpha, &layers);
+        let linewidth_scale =
+            train_number_scale(&self.linewidth_scale, Aesthetic::Linewidth, &layers);
+        let shape_scale = train_name_scale(&self.shape_scale, Aesthetic::Shape, &layers);
+        let linetype_scale = train_name_scale(&self.linetype_scale, Aesthetic::Linetype, &layers);
+        let panel = self.panel();
+
+        let mut commands = vec![DrawCommand::Rectangle {
+            position: Point::default(),
+            width: self.width as f64,
+            height: self.height as f64,
+            fill: Some(self.theme.background),
+            stroke: None,
+        }];
+        commands.push(DrawCommand::Rectangle {
+            position: Point {
+                x: panel.x,
+                y: panel.y,
+            },
+            width: panel.width,
+            height: panel.height,
+            fill: Some(self.theme.panel_background),
+            stroke: None,
+        });
+
+        self.draw_grid(&mut commands, &panel, x_scale, y_scale);
+
+        for ((layer, x), y) in layers.iter().zip(&x).zip(&y) {
+            let name = layer.geom.name();
+            let values = |aesthetic| layer.evaluated.get(aesthetic);
+            let n_rows = layer.evaluated.n_rows;
+            let data = ProcessedData {
+                x: x_scale.map_all(x),
+                y: y_scale.map_all(y),
+                color: resolve_colors(
+                    name,
+                    Aesthetic::Color,
+                    values(Aesthetic::Color),
+                    n_rows,
+                    &color_scale,
+                )?,
+                fill: resolve_colors(
+                    name,
+                    Aesthetic::Fill,
+                    values(Aesthetic::Fill),
+                    n_rows,
+                    &fill_scale,
+                )?,
+                size: resolve_numbers(
+                    Aesthetic::Size,
+                    values(Aesthetic::Size),
+                    n_rows,
+                    &size_scale,
+                ),
+                alpha: resolve_numbers(
+                    Aesthetic::Alpha,
+                    values(Aesthetic::Alpha),
+                    n_rows,
+                    &alpha_scale,
+                ),
+                linewidth: resolve_numbers(
+                    Aesthetic::Linewidth,
+                    values(Aesthetic::Linewidth),
+                    n_rows,
+                    &linewidth_scale,
+                ),
+                shape: resolve_names(
+                    name,
+                    Aesthetic::Shape,
+                    values(Aesthetic::Shape),
+                    n_rows,
+                    &shape_scale,
+                    Shape::from_name,
+                )?,
+                linetype: resolve_names(
+                    name,
+                    Aesthetic::Linetype,
+                    values(Aesthetic::Linetype),
+                    n_rows,
+                    &linetype_scale,
+                    Linetype::from_name,
+                )?,
+                group: layer.group.clone(),
+            };
+            commands.extend(layer.geom.draw(&data, self.coord.as_ref(), &panel));
+        }
+
+        self.draw_axes(&mut commands, &panel, x_scale, y_scale);
+        Ok((commands, warnings))
+    }
+
+    // builds the plot and writes it to `path`, the renderer picked by the file extension;
+    // PNGs don't have text yet (see PngRenderer)
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<Vec<PlotWarning>, PlotError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let renderer = match extension.as_str() {
+            "png" => PngRenderer::new(self.width, self.height, &path.to_string_lossy()),
+            _ => return Err(PlotError::UnsupportedFormat(extension)),
+        };
+        let (commands, warnings) = self.build_with_warnings()?;
+        renderer.render(&commands)?;
+        Ok(warnings)
+    }
+
+    fn prepare_layer<'l>(
+        &self,
+        layer: &'l Layer,
+        warnings: &mut Vec<PlotWarning>,
+    ) -> Result<PreparedLayer<'l>, PlotError> {
+        let geom = layer.geom();
+        let name = geom.name();
+        let data = layer
+            .get_data()
+            .or(self.data.as_ref())
+            .ok_or_else(|| PlotError::NoData {
+                layer: name.to_string(),
+            })?;
+
+        let mapping = layer
+            .get_mapping()
+            .inherit(&self.mapping)
+            .inherit(&geom.default_aes());
+        #[cfg(feature = "polars")]
+        let collected;
+        let data: &dyn DataSourceSelf = match data {
+            LayerData::Source(source) => source.as_ref(),
+            #[cfg(feature = "polars")]
+            LayerData::Lazy(query) => {
+                collected = query.collect_columns(&mapping.referenced_columns())?;
+                &collected
+            }
+        };
+        let aesthetic_warnings = validate_mapping(
+            name,
+            &mapping,
+            &geom.required_aes(),
+            &geom.optional_aes(),
+            data,
+        )
+        .into_result()?;
+        warnings.extend(aesthetic_warnings.into_iter().map(PlotWarning::from));
+
+        let mut evaluated = mapping.evaluate(data)?;
+        match self.missing_policy {
+            MissingPolicy::PropagateNan => {}
+            MissingPolicy::Error => {
+                if let Some(&(aesthetic, count)) = evaluated.missing_counts().first() {
+                    return Err(PlotError::Data(DataError::MissingValues {
+                        column: mapped_name(&mapping, aesthetic),
+                        count,
+                    }));
+                }
+            }
+            MissingPolicy::DropWithWarning => {
+                let complete = evaluated.complete_rows();
+                let count = complete.iter().filter(|&&complete| !complete).count();
+                if count > 0 {
+                    evaluated.retain_rows(&complete);
+                    warnings.push(PlotWarning::MissingValues {
+                        layer: name.to_string(),
+                        count,
+                    });
+                }
+            }
+        }
+        let n_rows = evaluated.n_rows;
+        Ok(PreparedLayer {
+            geom,
+            x: positions(evaluated.get(Aesthetic::X), n_rows),
+            y: positions(evaluated.get(Aesthetic::Y), n_rows),
+            x_time: temporal_kind(&mapping, Aesthetic::X, data),
+            y_time: temporal_kind(&mapping, Aesthetic::Y, data),
+            group: resolve_groups(&grouping_values(&mapping, &evaluated), n_rows),
+            evaluated,
+        })
+    }
+
+    fn panel(&self) -> Rect {
+        let [top, right, bottom, left] = self.theme.margin;
+        let top = match self.labels.title {
+            Some(_) => top + self.theme.title_size as f64 * 2.0,
+            None => top,
+        };
+        Rect {
+            x: left,
+            y: top,
+            width: (self.width as f64 - left - right).max(1.0),
+            height: (self.height as f64 - top - bottom).max(1.0),
+        }
+    }
+
+    // (start, end) of the line across the panel at `position` along the x or y axis
+    fn panel_line(&self, panel: &Rect, along_x: bool, position: f64) -> (Point, Point) {
+        let coord = self.coord.as_ref();
+        if along_x {
+            (
+                coord.transform(position, 0.0, panel),
+                coord.transform(position, 1.0, panel),
+            )
+        } else {
+            (
+                coord.transform(0.0, position, panel),
+                coord.transform(1.0, position, panel),
+            )
+        }
+    }
+
+    fn draw_grid(
+        &self,
+        commands: &mut Vec<DrawCommand>,
+        panel: &Rect,
+        x_scale: &dyn Scale,
+        y_scale: &dyn Scale,
+    ) {
+        let theme = &self.theme;
+        // minor lines first, so the major ones are drawn over them
+        let styles = [
+            (theme.minor_grid_color, theme.minor_grid_width, true),
+            (theme.grid_color, theme.grid_width, false),
+        ];
+        for (color, width, minor) in styles {
+            let Some(color) = color else {
+                continue;
+            };
+            for (along_x, scale) in [(true, x_scale), (false, y_scale)] {
+                let breaks = match minor {
+                    true => scale.get_minor_breaks(),
+                    false => scale.get_breaks(),
+                };
+                for b in breaks {
+                    let (start, end) = self.panel_line(panel, along_x, scale.map(b));
+                    commands.push(DrawCommand::Line {
+                        start,
+                        end,
+                        stroke: Some(Stroke {
+                            color: Some(color),
+                            width,
+                        }),
+                    });
+                }
+            }
+        }
+    }
+
+    // tick labels and axis titles, x along the bottom and y along the left
+    // (swapped when the coordinate system is flipped), plus the plot title
+    fn draw_axes(
+        &self,
+        commands: &mut Vec<DrawCommand>,
+        panel: &Rect,
+        x_scale: &dyn Scale,
+        y_scale: &dyn Scale,
+    ) {
+        let theme = &self.theme;
+        let text = |position, content: String, font_size| DrawCommand::Text {
+            position,
+            content,
+            font_size,
+            color: Some(theme.text_color),
+        };
+        let flipped = self.coord.is_flipped();
+
+        for (along_x, scale) in [(true, x_scale), (false, y_scale)] {
+            // the axis along the bottom edge of the panel
+            let horizontal = along_x != flipped;
+            for (b, label) in scale.get_breaks().into_iter().zip(scale.get_labels()) {
+                let (start, _) = self.panel_line(panel, along_x, scale.map(b));
+                let position = if horizontal {
+                    Point {
+                        x: start.x,
+                        y: panel.bottom() + theme.font_size as f64 * 1.5,
+                    }
+                } else {
+                    Point {
+                        x: panel.x - theme.font_size as f64 * 3.0,
+                        y: start.y,
+                    }
+                };
+                commands.push(text(position, label, theme.font_size));
+            }
+        }
+
+        let axis = |points: [Point; 2]| DrawCommand::Line {
+            start: points[0],
+            end: points[1],
+            stroke: Some(Stroke {
+                color: Some(theme.axis_color),
+                width: 1.0,
+            }),
+        };
+        let bottom_left = Point {
+            x: panel.x,
+            y: panel.bottom(),
+        };
+        commands.push(axis([
+            bottom_left,
+            Point {
+                x: panel.right(),
+                y: panel.bottom(),
+            },
+        ]));
+        commands.push(axis([
+            bottom_left,
+            Point {
+                x: panel.x,
+                y: panel.y,
+            },
+        ]));
+
+        // a scale's own name wins over the plot's labels
+        let x_label = x_scale.get_name().or(self.labels.x.as_deref());
+        let y_label = y_scale.get_name().or(self.labels.y.as_deref());
+        let (bottom_label, left_label) = match flipped {
+            false => (x_label, y_label),
+            true => (y_label, x_label),
+        };
+        if let Some(label) = bottom_label {
+            let position = Point {
+                x: panel.x + panel.width / 2.0,
+                y: panel.bottom() + theme.font_size as f64 * 3.2,
+            };
+            commands.push(text(position, label.to_string(), theme.font_size));
+        }
+        if let Some(label) = left_label {
+            let position = Point {
+                x: theme.font_size as f64,
+                y: panel.y + panel.height / 2.0,
+            };
+            commands.push(text(position, label.to_string(), theme.font_size));
+        }
+        if let Some(title) = &self.labels.title {
+            let position = Point {
+                x: panel.x,
+                y: theme.margin[0] + theme.title_size as f64,
+            };
+            commands.push(text(position, title.clone(), theme.title_size));
+        }
+    }
+}
+
+// a layer's evaluated aesthetics, waiting for the scales trained on every layer
+struct PreparedLayer<'a> {
+    geom: &'a dyn Geom,
+    evaluated: EvaluatedAesthetics,
+    x: Positions,
+    y: Positions,
+    // the kind of temporal column a position is mapped to, if any
+    x_time: Option<TemporalKind>,
+    y_time: Option<TemporalKind>,
+    group: Vec<usize>,
+}
+
+// positions in data units, or the levels of categorical ones
+enum Positions {
+    Continuous(Vec<f64>),
+    Discrete(Vec<DiscreteValue>),
+}
+
+impl PreparedLayer<'_> {
+    fn positions(&self, aesthetic: Aesthetic) -> (&Positions, &Option<TemporalKind>) {
+        match aesthetic {
+            Aesthetic::Y => (&self.y, &self.y_time),
+            _ => (&self.x, &self.x_time),
+        }
+    }
+}
+
+// what an aesthetic is mapped to, as written
+fn mapped_name(mapping: &AestheticMapping, aesthetic: Aesthetic) -> String {
+    match mapping.get(aesthetic) {
+        Some(AesValue::Mapped(expr)) => expr.to_string(),
+        _ => aesthetic.name().to_string(),
+    }
+}
+
+fn temporal_kind(
+    mapping: &AestheticMapping,
+    aesthetic: Aesthetic,
+    data: &dyn DataSourceSelf,
+) -> Option<TemporalKind> {
+    let Some(AesValue::Mapped(ColumnExpr::Column(name))) = mapping.get(aesthetic) else {
+        return None;
+    };
+    let temporal = data
+        .schema()
+        .iter()
+        .any(|column| &column.name == name && column.column_type == ColumnType::Temporal);
+    match temporal {
+        true => data
+            .get_temporal_column(name)
+            .ok()
+            .map(|column| column.kind),
+        false => None,
+    }
+}
+
+// a trained position scale and every layer's positions as numbers it maps
+type TrainedPositions = (Box<dyn Scale>, Vec<Vec<f64>>);
+
+// the plot's scale for a position, or one suiting the data, trained on every layer; the
+// positions come back as the numbers the scale maps, levels turned into theirs
+fn train_position_scale(
+    scale: Option<&dyn Scale>,
+    aesthetic: Aesthetic,
+    layers: &[PreparedLayer],
+    warnings: &mut Vec<PlotWarning>,
+) -> Result<TrainedPositions, PlotError> {
+    let discrete = layers
+        .iter()
+        .any(|layer| matches!(layer.positions(aesthetic).0, Positions::Discrete(_)));
+    let mut scale = match scale {
+        Some(scale) => scale.box_clone(),
+        None if discrete => Box::new(DiscreteScale::new()),
+        None => default_position_scale(
+            layers.iter().map(|layer| layer.positions(aesthetic).1),
+            warnings,
+        ),
+    };
+
+    // every level has to be known before any can be placed
+    if let Some(scale) = scale.as_discrete_mut() {
+        for layer in layers {
+            if let (Positions::Discrete(values), _) = layer.positions(aesthetic) {
+                scale.train_discrete(values);
+            }
+        }
+    }
+
+    let mut numbers = Vec::new();
+    for layer in layers {
+        let name = layer.geom.name();
+        let values = match (layer.positions(aesthetic).0, scale.as_discrete()) {
+            (Positions::Continuous(values), _) => values.clone(),
+            (Positions::Discrete(values), Some(discrete)) => values
+                .iter()
+                .map(|value| discrete.position(value))
+                .collect(),
+            (Positions::Discrete(_), None) => {
+                return Err(unsupported(
+                    name,
+                    aesthetic,
+                    "categorical positions need a discrete scale".to_string(),
+                ));
+            }
+        };
+        scale.train(&values);
+        let count = untransformable(scale.as_ref(), &values);
+        if count > 0 {
+            warnings.push(PlotWarning::Untransformable {
+                layer: name.to_string(),
+                aesthetic,
+                count,
+            });
+        }
+        numbers.push(values);
+    }
+    Ok((scale, numbers))
+}
+
+// the plot's scale for a colour aesthetic, or one suiting the data, trained on every layer
+fn train_color_scale(
+    scale: Option<&ColorScale>,
+    aesthetic: Aesthetic,
+    layers: &[PreparedLayer],
+) -> ColorScale {
+    let mapped: Vec<&[AestheticValue]> = layers
+        .iter()
+        .filter_map(|layer| match layer.evaluated.get(aesthetic) {
+            Some(AestheticValues::Mapped(values)) => Some(values.as_slice()),
+            _ => None,
+        })
+        .collect();
+    let mut scale = match scale {
+        Some(ColorScale::Gradient(scale)) => ColorScale::Gradient(scale.untrained()),
+        Some(scale) => scale.clone(),
+        None if mapped.iter().all(|values| is_continuous(values)) => {
+            ColorScale::Gradient(GradientScale::new())
+        }
+        None => ColorScale::Discrete(DiscreteScale::new()),
+    };
+
+    for values in mapped {
+        match &mut scale {
+            ColorScale::Gradient(scale) if is_continuous(values) => scale.train(&numbers(values)),
+            // categorical values with a gradient are reported when they're resolved
+            ColorScale::Gradient(_) => {}
+            ColorScale::Discrete(scale) => {
+                let levels: Vec<DiscreteValue> = values.iter().map(DiscreteValue::from).collect();
+                scale.train_discrete(&levels);
+            }
+            ColorScale::Manual(_) | ColorScale::Identity(_) => {}
+        }
+    }
+    scale
+}
+
+// the plot's scale for a numeric aesthetic, trained on every layer
+fn train_number_scale(
+    scale: &NumberScale,
+    aesthetic: Aesthetic,
+    layers: &[PreparedLayer],
+) -> NumberScale {
+    let mut scale = scale.untrained();
+    for layer in layers {
+        if let Some(AestheticValues::Mapped(values)) = layer.evaluated.get(aesthetic) {
+            scale.train(values);
+        }
+    }
+    scale
+}
+
+// the plot's scale for shapes or line types, trained on the levels every layer maps
+fn train_name_scale(
+    scale: &NameScale,
+    aesthetic: Aesthetic,
+    layers: &[PreparedLayer],
+) -> NameScale {
+    let mut scale = scale.clone();
+    for layer in layers {
+        if let Some(AestheticValues::Mapped(values)) = layer.evaluated.get(aesthetic) {
+            scale.train(values);
+        }
+    }
+    scale
+}
+
+fn numbers(values: &[AestheticValue]) -> Vec<f64> {
+    values
+        .iter()
+        .map(|value| value.as_numeric().unwrap_or(f64::NAN))
+        .collect()
+}
+
+// a time scale if the first layer mapped to a temporal column has dates or datetimes,
+// in the datetimes' time zone; durations are plain seconds
+fn default_position_scale<'a>(
+    kinds: impl IntoIterator<Item = &'a Option<TemporalKind>>,
+    warnings: &mut Vec<PlotWarning>,
+) -> Box<dyn Scale> {
+    match kinds.into_iter().flatten().next() {
+        Some(TemporalKind::Date) => Box::new(TimeScale::new()),
+        Some(TemporalKind::Datetime {
+            time_zone: Some(zone),
+            ..
+        }) => match zone.parse::<Tz>() {
+            Ok(time_zone) => Box::new(TimeScale::new().time_zone(time_zone)),
+            Err(_) => {
+                warnings.push(PlotWarning::UnknownTimeZone(zone.clone()));
+                Box::new(TimeScale::new())
+            }
+        },
+        Some(TemporalKind::Datetime { .. }) => Box::new(TimeScale::new()),
+        _ => Box::new(ContinuousScale::new()),
+    }
+}
+
+// how many values a scale's transformation can't take (e.g. zero on a log scale); they
+// aren't drawn
+fn untransformable(scale: &dyn Scale, values: &[f64]) -> usize {
+    values
+        .iter()
+        .filter(|value| value.is_finite() && !scale.transform(**value).is_finite())
+        .count()
+}
+
+fn unsupported(layer: &str, aesthetic: Aesthetic, message: String) -> PlotError {
+    PlotError::UnsupportedMapping {
+        layer: layer.to_string(),
+        aesthetic,
+        message,
+    }
+}
+
+// numbers stay continuous; text, booleans and the like are levels
+fn positions(values: Option<&AestheticValues>, n_rows: usize) -> Positions {
+    let Some(values) = values else {
+        return Positions::Continuous(vec![f64::NAN; n_rows]);
+    };
+    let values: Vec<AestheticValue> = (0..n_rows).map(|row| values.value(row).clone()).collect();
+    match is_continuous(&values) {
+        true => Positions::Continuous(
+            values
+                .iter()
+                .map(|value| value.as_numeric().unwrap_or(f64::NAN))
+                .collect(),
+        ),
+        false => Positions::Discrete(values.iter().map(DiscreteValue::from).collect()),
+    }
+}
+
+fn is_continuous(values: &[AestheticValue]) -> bool {
+    values
+        .iter()
+        .all(|value| matches!(value, AestheticValue::Numeric(_) | AestheticValue::Missing))
+}
+
+fn fixed_color(
+    layer: &str,
+    aesthetic: Aesthetic,
+    value: &AestheticValue,
+) -> Result<Color, PlotError> {
+    match value {
+        AestheticValue::Color(color) => Ok(*color),
+        AestheticValue::Text(name) => Color::parse(name)
+            .ok_or_else(|| unsupported(layer, aesthetic, format!("unknown colour '{}'", name))),
+        AestheticValue::Missing => Ok(Color::NA),
+        other => Err(unsupported(
+            layer,
+            aesthetic,
+            format!("{:?} is not a colour", other),
+        )),
+    }
+}
+
+fn resolve_colors(
+    layer: &str,
+    aesthetic: Aesthetic,
+    values: Option<&AestheticValues>,
+    n_rows: usize,
+    scale: &ColorScale,
+) -> Result<Vec<Color>, PlotError> {
+    let values = match values {
+        None => return Ok(vec![Color::default(); n_rows]),
+        Some(AestheticValues::Fixed(value)) => {
+            return Ok(vec![fixed_color(layer, aesthetic, value)?; n_rows]);
+        }
+        Some(AestheticValues::Mapped(values)) => values,
+    };
+
+    match scale {
+        ColorScale::Gradient(scale) if is_continuous(values) => {
+            Ok(scale.map_colors(&numbers(values)))
+        }
+        ColorScale::Gradient(_) => Err(unsupported(
+            layer,
+            aesthetic,
+            "categorical colours need a discrete scale".to_string(),
+        )),
+        ColorScale::Discrete(scale) => Ok(values
+            .iter()
+            .map(|value| scale.map_color(&DiscreteValue::from(value)))
+            .collect()),
+        ColorScale::Manual(scale) => Ok(values
+            .iter()
+            .map(|value| {
+                scale
+                    .map(&DiscreteValue::from(value))
+                    .copied()
+                    .unwrap_or(Color::NA)
+            })
+            .collect()),
+        // data that isn't a colour is an error, as it would be as a fixed value
+        ColorScale::Identity(scale) => values
+            .iter()
+            .map(|value| match scale.map_color(value) {
+                Some(color) => Ok(color),
+                None => fixed_color(layer, aesthetic, value),
+            })
+            .collect(),
+    }
+}
+
+// fixed numbers are used as they are, mapped ones go through the scale; values without
+// an output get the range scale's NA value or the aesthetic's default
+fn resolve_numbers(
+    aesthetic: Aesthetic,
+    values: Option<&AestheticValues>,
+    n_rows: usize,
+    scale: &NumberScale,
+) -> Vec<f64> {
+    let fallback = match scale {
+        NumberScale::Range(scale) => scale.get_na_value(),
+        _ => aesthetic
+            .default_value()
+            .and_then(|value| value.as_numeric())
+            .unwrap_or(f64::NAN),
+    };
+    match values {
+        None => vec![fallback; n_rows],
+        Some(AestheticValues::Fixed(value)) => vec![value.as_numeric().unwrap_or(fallback); n_rows],
+        Some(AestheticValues::Mapped(values)) => values
+            .iter()
+            .map(|value| scale.map(value).unwrap_or(fallback))
+            .collect(),
+    }
+}
+
+// shapes or line types: fixed values are names, mapped ones get theirs from the scale;
+// unknown names are an error, and values without a name aren't drawn
+fn resolve_names<T: Copy>(
+    layer: &str,
+    aesthetic: Aesthetic,
+    values: Option<&AestheticValues>,
+    n_rows: usize,
+    scale: &NameScale,
+    parse: fn(&str) -> Option<T>,
+) -> Result<Vec<Option<T>>, PlotError> {
+    let named = |name: &str| {
+        parse(name).map(Some).ok_or_else(|| {
+            unsupported(
+                layer,
+                aesthetic,
+                format!("unknown {} '{}'", aesthetic, name),
+            )
+        })
+    };
+    let fixed = |value: &AestheticValue| match value {
+        AestheticValue::Text(name) => named(name),
+        AestheticValue::Missing => Ok(None),
+        other => Err(unsupported(
+            layer,
+            aesthetic,
+            format!("{:?} is not a {} name", other, aesthetic),
+        )),
+    };
+
+    match values {
+        None => {
+            let default = aesthetic.default_value().unwrap_or(AestheticValue::Missing);
+            Ok(vec![fixed(&default)?; n_rows])
+        }
+        Some(AestheticValues::Fixed(value)) => Ok(vec![fixed(value)?; n_rows]),
+        Some(AestheticValues::Mapped(values)) => values
+            .iter()
+            .map(|value| {
+                let name = match aesthetic {
+                    Aesthetic::Linetype => scale.map_linetype(value),
+                    _ => scale.map_shape(value),
+                };
+                name.map_or(Ok(None), named)
+            })
+            .collect(),
+    }
+}
+
+// mapped values that split a layer into groups: the group aesthetic itself and any
+// other discrete mapping apart from positions and labels
+fn grouping_values<'a>(
+    mapping: &AestheticMapping,
+    evaluated: &'a EvaluatedAesthetics,
+) -> Vec<&'a [AestheticValue]> {
+    mapping
+        .aesthetics()
+        .filter(|aesthetic| !matches!(aesthetic, Aesthetic::X | Aesthetic::Y | Aesthetic::Label))
+        .filter_map(|aesthetic| match evaluated.get(aesthetic) {
+            Some(AestheticValues::Mapped(values))
+                if aesthetic == Aesthetic::Group || !is_continuous(values) =>
+            {
+                Some(values.as_slice())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// rows sharing every grouping value form a group, numbered in level order
+fn resolve_groups(discrete: &[&[AestheticValue]], n_rows: usize) -> Vec<usize> {
+    let key_columns: Vec<Vec<DiscreteValue>> = discrete
+        .iter()
+        .map(|values| values.iter().map(DiscreteValue::from).collect())
+        .collect();
+    let mut group = vec![0; n_rows];
+    for (number, (_, rows)) in group_rows(&key_columns, n_rows).into_iter().enumerate() {
+        for row in rows {
+            group[row] = number;
+        }
+    }
+    group
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source_grid::GridSource;
+    use crate::scale_manual::{IdentityScale, ManualScale};
+    use crate::scales::Expansion;
+
+    // x in 0..=4 along columns, y in {0, 1} along rows, z = x * (y + 1)
+    fn grid() -> GridSource {
+        GridSource::from_fn(&[0.0, 1.0, 2.0, 3.0, 4.0], &[0.0, 1.0], |row, column| {
+            column as f64 * (row as f64 + 1.0)
+        })
+    }
+
+    fn circles(commands: &[DrawCommand]) -> Vec<(Point, f64, Color)> {
+        commands
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Circle {
+                    position,
+                    radius,
+                    fill: Some(fill),
+                    ..
+                } => Some((*position, *radius, *fill)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_points_fill_the_expanded_panel() {
+        let plot = Plot::new()
+            .data(grid())
+            .geom_point(aes().x("x").y("z").set(Aesthetic::Color, "red"))
+            .size(200, 100);
+        let points = circles(&plot.build().unwrap());
+        assert_eq!(points.len(), 10);
+
+        let panel = plot.panel();
+        // 5% expansion on each side of the trained range
+        let left = panel.x + panel.width * 0.05 / 1.1;
+        assert!((points[0].0.x - left).abs() < 1e-9);
+        assert!(points.iter().all(|(position, radius, color)| {
+            position.x > panel.x
+                && position.x < panel.right()
+                && *radius == 3.0
+                && *color == Color::rgb(255, 0, 0)
+        }));
+    }
+
+    #[test]
+    fn test_plots_and_layers_borrow_their_data() {
+        let data = grid();
+        let owned = Plot::new().data(grid()).geom_point(aes().x("x").y("z"));
+        let borrowed = Plot::new().data(&data).geom_point(aes().x("x").y("z"));
+        let layer = Plot::new().layer(
+            Layer::new(GeomPoint)
+                .data(&data)
+                .mapping(aes().x("x").y("z")),
+        );
+
+        let expected = circles(&owned.build().unwrap());
+        assert_eq!(circles(&borrowed.build().unwrap()), expected);
+        assert_eq!(circles(&layer.build().unwrap()), expected);
+    }
+
+    #[test]
+    fn test_layers_inherit_plot_mapping_and_map_visuals() {
+        let plot = Plot::new()
+            .data(grid())
+            .mapping(aes().x("x").y("y"))
+            .geom_point(aes().size("z"))
+            .geom_line(aes().group("y"))
+            .x_scale(
+                ContinuousScale::new()
+                    .limits(0.0, 4.0)
+                    .expand(Expansion::none()),
+            );
+        let commands = plot.build().unwrap();
+
+        let points = circles(&commands);
+        let radii: Vec<f64> = points.iter().map(|(_, radius, _)| *radius).collect();
+        assert_eq!(radii[0], 1.5);
+        assert_eq!(radii[9], 6.0);
+
+        // without expansion the limits are the edges of the panel
+        let panel = plot.panel();
+        assert_eq!(points[0].0.x, panel.x);
+        assert_eq!(points[4].0.x, panel.right());
+
+        // the black data lines: four segments in each of the two groups
+        let segments = commands
+            .iter()
+            .filter(|command| {
+                matches!(command, DrawCommand::Line { stroke: Some(Stroke { color: Some(color), .. }), .. }
+                    if *color == Color::default())
+            })
+            .count();
+        assert_eq!(segments, 8);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let no_data = Plot::new().geom_point(aes().x("x").y("y"));
+        assert_eq!(
+            no_data.build().unwrap_err().to_string(),
+            "Layer 'point' has no data and the plot has none to inherit"
+        );
+
+        let missing_y = Plot::new().data(grid()).geom_point(aes().x("x"));
+        assert!(matches!(
+            missing_y.build(),
+            Err(PlotError::Aesthetic(
+                AestheticError::MissingAesthetic { .. }
+            ))
+        ));
+
+        let bad_colour = Plot::new()
+            .data(grid())
+            .geom_point(aes().x("x").y("y").set(Aesthetic::Color, "reddish"));
+        assert_eq!(
+            bad_colour.build().unwrap_err().to_string(),
+            "Layer 'point', aesthetic 'color': unknown colour 'reddish'"
+        );
+
+        let plot = Plot::new().data(grid()).geom_point(aes().x("x").y("y"));
+        assert!(matches!(
+            plot.save("plot.svg"),
+            Err(PlotError::UnsupportedFormat(extension)) if extension == "svg"
+        ));
+    }
+
+    #[test]
+    fn test_save_png() {
+        let path =
+            std::env::temp_dir().join(format!("gracex_test_save_png_{}.png", std::process::id()));
+        Plot::new()
+            .data(grid())
+            .geom_point(aes().x("x").y("z").color("y"))
+            .title("grid")
+            .save(&path)
+            .unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_limits_censor_points_and_scale_names_label_axes() {
+        let commands = Plot::new()
+            .data(grid())
+            .geom_point(aes().x("x").y("z"))
+            .xlim(0.0, 2.0)
+            .y_scale(ContinuousScale::new().name("z value"))
+            .y_label("ignored")
+            .build()
+            .unwrap();
+
+        // x = 3 and x = 4 fall outside the limits in both rows
+        assert_eq!(circles(&commands).len(), 6);
+        assert!(commands.iter().any(|command| matches!(
+            command,
+            DrawCommand::Text { content, .. } if content == "z value"
+        )));
+        assert!(!commands.iter().any(|command| matches!(
+            command,
+            DrawCommand::Text { content, .. } if content == "ignored"
+        )));
+    }
+
+    #[test]
+    fn test_log_scale_drops_non_positive_values() {
+        let plot = Plot::new()
+            .data(grid())
+            .geom_point(aes().x("x").y("z"))
+            .x_scale(ContinuousScale::new().log10());
+        let (commands, warnings) = plot.build_with_warnings().unwrap();
+
+        // x = 0 in both rows can't be shown on a log scale
+        assert_eq!(circles(&commands).len(), 8);
+        assert_eq!(
+            warnings,
+            vec![PlotWarning::Untransformable {
+                layer: "point".to_string(),
+                aesthetic: Aesthetic::X,
+                count: 2,
+            }]
+        );
+        let labels: Vec<&str> = commands
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Text { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(labels.contains(&"1") && labels.contains(&"3"));
+    }
+
+    #[test]
+    fn test_colour_scales_are_trained_on_every_layer() {
+        let black = Color::rgb(0, 0, 0);
+        let white = Color::rgb(255, 255, 255);
+        let commands = Plot::new()
+            .data(grid())
+            .geom_point(aes().x("x").y("z").color("z"))
+            .geom_point(aes().x("x").y("y").color("y"))
+            .color_scale(GradientScale::gradient(black, white).limits(0.0, 4.0))
+            .build()
+            .unwrap();
+
+        let colours: Vec<Color> = circles(&commands).iter().map(|(.., fill)| *fill).collect();
+        // z runs 0..=4 in the first row and 0..=8 in the second, beyond the limits
+        assert_eq!(
+            &colours[..5],
+            &[
+                black,
+                Color::rgb(64, 64, 64),
+                Color::rgb(128, 128, 128),
+                Color::rgb(191, 191, 191),
+                white
+            ]
+        );
+        assert_eq!(colours[9], Color::NA);
+        // the second layer's y values share the first layer's scale
+        assert_eq!(colours[15], Color::rgb(64, 64, 64));
+    }
+
+    #[test]
+    fn test_size_scales_are_trained_on_every_layer() {
+        let commands = Plot::new()
+            .data(grid())
+            .geom_point(aes().x("x").y("z").size("z"))
+            .geom_point(aes().x("x").y("y").size("y"))
+            .size_scale(RangeScale::radius().range(2.0, 10.0))
+            .build()
+            .unwrap();
+
+        let radii: Vec<f64> = circles(&commands)
+            .iter()
+            .map(|(_, radius, _)| *radius)
+            .collect();
+        // z runs 0..=8, so the second layer's y of 1 is an eighth of the way along
+        assert_eq!(radii[4], 6.0);
+        assert_eq!(radii[9], 10.0);
+        assert_eq!(radii[15], 3.0);
+    }
+
+    #[test]
+    fn test_manual_and_identity_scales() {
+        let red = Color::rgb(255, 0, 0);
+        let commands = Plot::new()
+            .data(grid())
+            .geom_point(aes().x("x").y("y").color("y").size("z"))
+            .color_scale(ManualScale::new().value(1.0, red))
+            .size_scale(IdentityScale::new())
+            .build()
+            .unwrap();
+
+        let points = circles(&commands);
+        // y of 0 has no colour of its own and no fallback
+        assert_eq!(points[0].2, Color::NA);
+        assert_eq!(points[5].2, red);
+        // the radii are the z values themselves
+        let radii: Vec<f64> = points.iter().map(|(_, radius, _)| *radius).collect();
+        assert_eq!(&radii[5..], &[0.0, 2.0, 4.0, 6.0, 8.0]);
+    }
+
+    #[test]
+    fn test_missing_policy_applies_to_mapped_rows() {
+        use crate::data_sources::OwnedColumnSource;
+
+        let plot = |policy| {
+            Plot::new()
+                .data(OwnedColumnSource {
+                    name: "v".to_string(),
+                    data: vec![1.0, f64::NAN, 3.0],
+                })
+                .geom_point(aes().x("v").y("v"))
+                .missing_policy(policy)
+        };
+
+        let (commands, warnings) = plot(MissingPolicy::default())
+            .build_with_warnings()
+            .unwrap();
+        assert_eq!(circles(&commands).len(), 2);
+        assert_eq!(
+            warnings,
+            vec![PlotWarning::MissingValues {
+                layer: "point".to_string(),
+                count: 1,
+            }]
+        );
+
+        let (_, warnings) = plot(MissingPolicy::PropagateNan)
+            .build_with_warnings()
+            .unwrap();
+        assert!(warnings.is_empty());
+
+        assert_eq!(
+            plot(MissingPolicy::Error).build().unwrap_err().to_string(),
+            "Column 'v' has 1 missing values"
+        );
+    }
+
+    #[test]
+    fn test_groups_are_numbered_in_level_order() {
+        let numbers = [10.0, 2.0, 10.0, f64::NAN].map(AestheticValue::Numeric);
+        let kinds = ["b", "a", "a", "a"].map(|kind| AestheticValue::Text(kind.to_string()));
+        assert_eq!(resolve_groups(&[&numbers], 4), vec![1, 0, 1, 2]);
+        assert_eq!(resolve_groups(&[&numbers, &kinds], 4), vec![2, 0, 1, 3]);
+        assert_eq!(resolve_groups(&[], 4), vec![0; 4]);
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_discrete_colours_split_lines_into_groups() {
+        use polars::prelude::df;
+
+        let df = df! {
+            "x" => &[0.0, 1.0, 0.0, 1.0],
+            "y" => &[0.0, 1.0, 1.0, 0.0],
+            "kind" => &["a", "a", "b", "b"],
+        }
+        .unwrap();
+        let commands = Plot::new()
+            .data(df)
+            .geom_line(aes().x("x").y("y").color("kind"))
+            .theme(Theme {
+                grid_color: None,
+                minor_grid_color: None,
+                ..Theme::minimal()
+            })
+            .build()
+            .unwrap();
+
+        let colours: Vec<Color> = commands
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Line {
+                    stroke:
+                        Some(Stroke {
+                            color: Some(color), ..
+                        }),
+                    ..
+                } => Some(*color),
+                _ => None,
+            })
+            .collect();
+        // one segment per kind, then the two axis lines
+        assert_eq!(colours.len(), 4);
+        assert_ne!(colours[0], colours[1]);
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_shape_and_linetype_scales() {
+        use polars::prelude::df;
+
+        let df = df! {
+            "x" => &[0.0, 1.0, 0.0, 1.0],
+            "y" => &[0.0, 1.0, 1.0, 0.0],
+            "kind" => &["a", "a", "b", "b"],
+        }
+        .unwrap();
+        let plot = Plot::new()
+            .data(&df)
+            .geom_point(aes().x("x").y("y").shape("kind"))
+            .geom_line(aes().x("x").y("y").linetype("kind"))
+            .shape_scale(DiscreteScale::new().shapes(["square", "circle"]))
+            .linetype_scale(DiscreteScale::new().linetypes(["solid", "dashed"]))
+            .theme(Theme {
+                grid_color: None,
+                minor_grid_color: None,
+                ..Theme::minimal()
+            });
+        let commands = plot.build().unwrap();
+
+        let squares = commands
+            .iter()
+            .filter(|command| matches!(command, DrawCommand::Polygon { points, .. } if points.len() == 4))
+            .count();
+        assert_eq!((squares, circles(&commands).len()), (2, 2));
+        // a solid segment for "a", several dashes for "b", then the two axis lines
+        let lines = commands
+            .iter()
+            .filter(|command| matches!(command, DrawCommand::Line { .. }))
+            .count();
+        assert!(lines > 4);
+
+        // "b" becomes a square, "a" has no shape and isn't drawn
+        let manual = Plot::new()
+            .data(&df)
+            .geom_point(aes().x("x").y("y").shape("kind"))
+            .shape_scale(ManualScale::new().value("b", "square".to_string()))
+            .build()
+            .unwrap();
+        let polygons = manual
+            .iter()
+            .filter(|command| matches!(command, DrawCommand::Polygon { .. }))
+            .count();
+        assert_eq!((polygons, circles(&manual).len()), (2, 0));
+
+        let unknown = Plot::new()
+            .data(&df)
+            .geom_point(aes().x("x").y("y").shape("kind"))
+            .shape_scale(IdentityScale::new());
+        assert_eq!(
+            unknown.build().unwrap_err().to_string(),
+            "Layer 'point', aesthetic 'shape': unknown shape 'a'"
+        );
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_categorical_positions_use_a_discrete_scale() {
+        use polars::prelude::df;
+
+        let df = df! {
+            "kind" => &["b", "a", "c", "a"],
+            "value" => &[1.0, 2.0, 3.0, 4.0],
+        }
+        .unwrap();
+        let plot = Plot::new()
+            .data(df)
+            .geom_point(aes().x("kind").y("value").color("kind"))
+            .size(200, 100)
+            .theme(Theme {
+                margin: [0.0; 4],
+                ..Theme::default()
+            });
+
+        // levels at 1, 2 and 3 with 0.6 either side: 0.4..3.6 over 200 pixels
+        let xs: Vec<f64> = circles(&plot.build().unwrap())
+            .iter()
+            .map(|(position, ..)| (position.x * 1e6).round() / 1e6)
+            .collect();
+        assert_eq!(xs, vec![100.0, 37.5, 162.5, 37.5]);
+
+        // explicit levels set the order and leave out other values
+        let commands = plot
+            .x_scale(DiscreteScale::new().levels(["c", "b"]))
+            .build()
+            .unwrap();
+        let points = circles(&commands);
+        assert_eq!(points.len(), 2);
+        assert!(points[0].0.x > points[1].0.x);
+        assert_ne!(points[0].2, points[1].2);
+
+        let continuous = Plot::new()
+            .data(df! { "kind" => &["a"], "value" => &[1.0] }.unwrap())
+            .geom_point(aes().x("kind").y("value"))
+            .xlim(0.0, 1.0);
+        assert!(matches!(
+            continuous.build(),
+            Err(PlotError::UnsupportedMapping { .. })
+        ));
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_date_columns_get_a_time_scale() {
+        use polars::prelude::{DataFrame, DataType, NamedFrom, Series};
+
+        // 2024-01-01, 2024-03-01 and 2024-05-01 as days since the epoch
+        let days = Series::new("day".into(), &[19_723i32, 19_783, 19_844])
+            .cast(&DataType::Date)
+            .unwrap();
+        let values = Series::new("value".into(), &[1.0, 3.0, 2.0]);
+        let df = DataFrame::new(vec![days.into(), values.into()]).unwrap();
+
+        let commands = Plot::new()
+            .data(df)
+            .geom_line(aes().x("day").y("value"))
+            .build()
+            .unwrap();
+        let labels: Vec<&str> = commands
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Text { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        for month in ["Jan 2024", "Feb 2024", "Mar 2024", "Apr 2024", "May 2024"] {
+            assert!(labels.contains(&month), "{:?}", labels);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+// The types needed to build and save a plot from any supported data source.
+// `use gracex::prelude::*;` brings them all into scope.
+
+pub use crate::aesthetics::{AesValue, Aesthetic, AestheticMapping, AestheticValue, aes};
+pub use crate::coord::{CoordCartesian, CoordFlip, CoordSystem};
+pub use crate::data_expression::ColumnExpr;
+pub use crate::data_groups::{DiscreteValue, group_by};
+pub use crate::data_missing::MissingPolicy;
+pub use crate::data_source_borrowed::DataSourceBorrowed;
+pub use crate::data_source_chunked::DataSourceChunked;
+pub use crate::data_source_grid::GridSource;
+pub use crate::data_source_owned::DataSourceOwned;
+pub use crate::data_source_records::{DataSourceRecord, FieldValue, RecordColumns};
+pub use crate::data_source_self::DataSourceSelf;
+pub use crate::data_source_view::RowView;
+pub use crate::data_sources::{ColumnSchema, ColumnType, DataError};
+pub use crate::geom::{Geom, GeomLine, GeomPoint, Linetype, Shape};
+pub use crate::layer::{Layer, LayerData};
+pub use crate::plot::{Plot, PlotError, PlotWarning};
+pub use crate::primitives::{Color, DrawCommand, Point, Stroke};
+pub use crate::renderer::{PngRenderer, Renderer};
+pub use crate::scale_breaks::{Breaks, MinorBreaks};
+pub use crate::scale_color::{ColorScale, GradientScale};
+pub use crate::scale_discrete::{DiscreteScale, NameScale};
+pub use crate::scale_labels::LabelFormat;
+pub use crate::scale_manual::{IdentityScale, ManualScale};
+pub use crate::scale_range::{NumberScale, RangeScale};
+pub use crate::scale_time::{
+    CalendarUnit, TimeBreaks, TimeInterval, TimeLabels, TimeScale, epoch_seconds,
+};
+pub use crate::scale_transform::ScaleTransform;
+pub use crate::scales::{ContinuousScale, Expansion, OutOfBounds, Scale};
+pub use crate::theme::Theme;
+
+#[cfg(feature = "derive")]
+pub use crate::data_source_records::DataSource;
+
+#[cfg(feature = "polars")]
+pub use crate::data_loaders::read_file;
+#[cfg(feature = "polars")]
+pub use crate::data_source_lazy::LazyFrameSource;
+
+#[cfg(feature = "nalgebra")]
+pub use crate::data_source_nalgebra::MatrixColumnSource;
+#[cfg(feature = "ndarray")]
+pub use crate::data_source_ndarray::ArrayColumnSource;
@@ -1,8 +1,10 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -10,11 +12,13 @@ pub struct Color {
     pub a: u8,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Stroke {
     pub color: Option<Color>,
     pub width: f64,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum DrawCommand {
     Circle {
         position: Point,
@@ -78,3 +82,72 @@ impl Default for Color {
         }
     }
 }
+
+impl Color {
+    // drawn for missing values
+    pub const NA: Color = Color {
+        r: 128,
+        g: 128,
+        b: 128,
+        a: 255,
+    };
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b, a: 255 }
+    }
+
+    // the same colour with its opacity multiplied by `alpha` (0 to 1)
+    pub fn with_alpha(self, alpha: f64) -> Self {
+        let a = (self.a as f64 * alpha.clamp(0.0, 1.0)).round() as u8;
+        Color { a, ..self }
+    }
+
+    // "#rrggbb", "#rrggbbaa", "#rgb" or a basic colour name
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if let Some(hex) = text.strip_prefix('#') {
+            let channel = |index: usize, width: usize| {
+                let digits = hex.get(index * width..(index + 1) * width)?;
+                let value = u8::from_str_radix(digits, 16).ok()?;
+                // "#abc" is short for "#aabbcc"
+                Some(if width == 1 { value * 17 } else { value })
+            };
+            return match hex.len() {
+                3 => Some(Color::rgb(channel(0, 1)?, channel(1, 1)?, channel(2, 1)?)),
+                6 => Some(Color::rgb(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+                8 => Some(Color {
+                    r: channel(0, 2)?,
+                    g: channel(1, 2)?,
+                    b: channel(2, 2)?,
+                    a: channel(3, 2)?,
+                }),
+                _ => None,
+            };
+        }
+
+        let color = match text.to_lowercase().as_str() {
+            "black" => Color::rgb(0, 0, 0),
+            "white" => Color::rgb(255, 255, 255),
+            "red" => Color::rgb(255, 0, 0),
+            "green" => Color::rgb(0, 128, 0),
+            "blue" => Color::rgb(0, 0, 255),
+            "yellow" => Color::rgb(255, 255, 0),
+            "orange" => Color::rgb(255, 165, 0),
+            "purple" => Color::rgb(128, 0, 128),
+            "pink" => Color::rgb(255, 192, 203),
+            "brown" => Color::rgb(165, 42, 42),
+            "grey" | "gray" => Color::rgb(190, 190, 190),
+            "darkgrey" | "darkgray" => Color::rgb(169, 169, 169),
+            "steelblue" => Color::rgb(70, 130, 180),
+            "navy" => Color::rgb(0, 0, 128),
+            "transparent" => Color {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 0,
+            },
+            _ => return None,
+        };
+        Some(color)
+    }
+}
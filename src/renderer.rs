@@ -6,6 +6,8 @@ pub trait Renderer {
     fn render(&self, commands: &[DrawCommand]) -> Result<(), std::io::Error>;
 }
 
+// draws shapes and lines; there is no font rasteriser yet, so Text commands (tick
+// labels, axis titles, the title) are skipped and the PNG has no text
 pub struct PngRenderer {
     width: u32, // in px
     height: u32,
@@ -36,9 +38,10 @@ impl PngRenderer {
 
     /// Helper: Create a tiny-skia Stroke from our Stroke
     fn create_stroke(stroke: &crate::primitives::Stroke) -> Option<SkiaStroke> {
-        let mut skia_stroke = SkiaStroke::default();
-        skia_stroke.width = stroke.width as f32;
-        Some(skia_stroke)
+        Some(SkiaStroke {
+            width: stroke.width as f32,
+            ..SkiaStroke::default()
+        })
     }
 
     fn draw_circle(
@@ -56,7 +59,7 @@ impl PngRenderer {
         let r = radius as f32;
         let cx = position.x as f32;
         let cy = position.y as f32;
-        let k = 0.5522847498; // Magic constant for circle approximation
+        let k = 0.552_284_8; // Magic constant for circle approximation
         let kr = k * r;
 
         path.move_to(cx - r, cy);
@@ -66,9 +69,9 @@ impl PngRenderer {
         path.cubic_to(cx - kr, cy + r, cx - r, cy + kr, cx - r, cy);
         path.close();
 
-        let path = path.finish().ok_or_else(|| {
-            std::io::Error::new(std::io::ErrorKind::Other, "Failed to build circle path")
-        })?;
+        let path = path
+            .finish()
+            .ok_or_else(|| std::io::Error::other("Failed to build circle path"))?;
 
         // Fill if specified
         if let Some(fill_color) = fill {
@@ -83,14 +86,13 @@ impl PngRenderer {
         }
 
         // Stroke if specified
-        if let Some(stroke_spec) = stroke {
-            if let Some(stroke_color) = &stroke_spec.color {
-                let paint = Self::create_paint(stroke_color);
-                let skia_stroke = Self::create_stroke(stroke_spec).ok_or_else(|| {
-                    std::io::Error::new(std::io::ErrorKind::Other, "Failed to create stroke")
-                })?;
-                pixmap.stroke_path(&path, &paint, &skia_stroke, Transform::identity(), None);
-            }
+        if let Some(stroke_spec) = stroke
+            && let Some(stroke_color) = &stroke_spec.color
+        {
+            let paint = Self::create_paint(stroke_color);
+            let skia_stroke = Self::create_stroke(stroke_spec)
+                .ok_or_else(|| std::io::Error::other("Failed to create stroke"))?;
+            pixmap.stroke_path(&path, &paint, &skia_stroke, Transform::identity(), None);
         }
 
         Ok(())
@@ -107,18 +109,17 @@ impl PngRenderer {
         path.move_to(start.x as f32, start.y as f32);
         path.line_to(end.x as f32, end.y as f32);
 
-        let path = path.finish().ok_or_else(|| {
-            std::io::Error::new(std::io::ErrorKind::Other, "Failed to build line path")
-        })?;
-
-        if let Some(stroke_spec) = stroke {
-            if let Some(stroke_color) = &stroke_spec.color {
-                let paint = Self::create_paint(stroke_color);
-                let skia_stroke = Self::create_stroke(stroke_spec).ok_or_else(|| {
-                    std::io::Error::new(std::io::ErrorKind::Other, "Failed to create stroke")
-                })?;
-                pixmap.stroke_path(&path, &paint, &skia_stroke, Transform::identity(), None);
-            }
+        let path = path
+            .finish()
+            .ok_or_else(|| std::io::Error::other("Failed to build line path"))?;
+
+        if let Some(stroke_spec) = stroke
+            && let Some(stroke_color) = &stroke_spec.color
+        {
+            let paint = Self::create_paint(stroke_color);
+            let skia_stroke = Self::create_stroke(stroke_spec)
+                .ok_or_else(|| std::io::Error::other("Failed to create stroke"))?;
+            pixmap.stroke_path(&path, &paint, &skia_stroke, Transform::identity(), None);
         }
 
         Ok(())
@@ -145,9 +146,9 @@ impl PngRenderer {
         path.line_to(x, y + h);
         path.close();
 
-        let path = path.finish().ok_or_else(|| {
-            std::io::Error::new(std::io::ErrorKind::Other, "Failed to build rectangle path")
-        })?;
+        let path = path
+            .finish()
+            .ok_or_else(|| std::io::Error::other("Failed to build rectangle path"))?;
 
         // Fill if specified
         if let Some(fill_color) = fill {
@@ -162,14 +163,13 @@ impl PngRenderer {
         }
 
         // Stroke if specified
-        if let Some(stroke_spec) = stroke {
-            if let Some(stroke_color) = &stroke_spec.color {
-                let paint = Self::create_paint(stroke_color);
-                let skia_stroke = Self::create_stroke(stroke_spec).ok_or_else(|| {
-                    std::io::Error::new(std::io::ErrorKind::Other, "Failed to create stroke")
-                })?;
-                pixmap.stroke_path(&path, &paint, &skia_stroke, Transform::identity(), None);
-            }
+        if let Some(stroke_spec) = stroke
+            && let Some(stroke_color) = &stroke_spec.color
+        {
+            let paint = Self::create_paint(stroke_color);
+            let skia_stroke = Self::create_stroke(stroke_spec)
+                .ok_or_else(|| std::io::Error::other("Failed to create stroke"))?;
+            pixmap.stroke_path(&path, &paint, &skia_stroke, Transform::identity(), None);
         }
 
         Ok(())
@@ -194,9 +194,9 @@ impl PngRenderer {
         }
         path.close();
 
-        let path = path.finish().ok_or_else(|| {
-            std::io::Error::new(std::io::ErrorKind::Other, "Failed to build polygon path")
-        })?;
+        let path = path
+            .finish()
+            .ok_or_else(|| std::io::Error::other("Failed to build polygon path"))?;
 
         // Fill if specified
         if let Some(fill_color) = fill {
@@ -211,14 +211,13 @@ impl PngRenderer {
         }
 
         // Stroke if specified
-        if let Some(stroke_spec) = stroke {
-            if let Some(stroke_color) = &stroke_spec.color {
-                let paint = Self::create_paint(stroke_color);
-                let skia_stroke = Self::create_stroke(stroke_spec).ok_or_else(|| {
-                    std::io::Error::new(std::io::ErrorKind::Other, "Failed to create stroke")
-                })?;
-                pixmap.stroke_path(&path, &paint, &skia_stroke, Transform::identity(), None);
-            }
+        if let Some(stroke_spec) = stroke
+            && let Some(stroke_color) = &stroke_spec.color
+        {
+            let paint = Self::create_paint(stroke_color);
+            let skia_stroke = Self::create_stroke(stroke_spec)
+                .ok_or_else(|| std::io::Error::other("Failed to create stroke"))?;
+            pixmap.stroke_path(&path, &paint, &skia_stroke, Transform::identity(), None);
         }
 
         Ok(())
@@ -228,9 +227,8 @@ impl PngRenderer {
 impl Renderer for PngRenderer {
     fn render(&self, commands: &[DrawCommand]) -> Result<(), std::io::Error> {
         // Create a pixmap (the canvas)
-        let mut pixmap = Pixmap::new(self.width, self.height).ok_or_else(|| {
-            std::io::Error::new(std::io::ErrorKind::Other, "Failed to create pixmap")
-        })?;
+        let mut pixmap = Pixmap::new(self.width, self.height)
+            .ok_or_else(|| std::io::Error::other("Failed to create pixmap"))?;
 
         // Fill with white background
         pixmap.fill(SkiaColor::WHITE);
@@ -278,25 +276,14 @@ impl Renderer for PngRenderer {
                 } => {
                     self.draw_polygon(&mut pixmap, points, fill.as_ref(), stroke.as_ref())?;
                 }
-                DrawCommand::Text {
-                    position,
-                    content,
-                    font_size: _,
-                    color: _,
-                } => {
-                    // Text rendering is complex, we'll skip for MWE
-                    // In a real implementation, you'd use a text shaping library
-                    println!(
-                        "Text rendering not yet implemented: {} at ({}, {})",
-                        content, position.x, position.y
-                    );
-                }
+                // not drawn, see PngRenderer
+                DrawCommand::Text { .. } => {}
             }
         }
 
         // Save to PNG
         pixmap
             .save_png(&self.file_path)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+            .map_err(|e| std::io::Error::other(e.to_string()))
     }
 }
@@ -0,0 +1,325 @@
+// Where the ticks of a continuous scale go. The default is the Extended algorithm of
+// Talbot, Lin and Hanrahan (2010), an extension of Wilkinson's, which scores candidate
+// sequences on simplicity, coverage of the data and density and keeps the best one.
+// Nice numbers after Heckbert (1990) are available too, as are fixed and manual breaks.
+
+// preferred step multiples, best first
+const Q: [f64; 6] = [1.0, 5.0, 2.0, 2.5, 4.0, 3.0];
+// weights for simplicity, coverage, density and legibility
+const WEIGHTS: [f64; 4] = [0.25, 0.2, 0.5, 0.05];
+const EPSILON: f64 = 1e-10;
+// no more breaks than this, whatever width was asked for
+const MAX_BREAKS: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Breaks {
+    // Extended breaks, aiming for about this many ticks
+    Extended(usize),
+    // Heckbert's nice numbers, aiming for about this many intervals
+    Nice(usize),
+    // every `width` data units, aligned to multiples of it
+    Width(f64),
+    Manual(Vec<f64>),
+    None,
+}
+
+impl Default for Breaks {
+    fn default() -> Self {
+        Breaks::Extended(5)
+    }
+}
+
+impl Breaks {
+    // breaks for a domain; a scale drops those that end up outside its limits
+    pub fn compute(&self, (min, max): (f64, f64)) -> Vec<f64> {
+        match self {
+            Breaks::Extended(n) => extended_breaks(min, max, *n),
+            Breaks::Nice(n) => nice_breaks(min, max, *n),
+            Breaks::Width(width) => width_breaks(min, max, *width),
+            Breaks::Manual(breaks) => breaks.clone(),
+            Breaks::None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MinorBreaks {
+    // this many evenly spaced ticks between neighbouring major breaks
+    Between(usize),
+    Manual(Vec<f64>),
+    None,
+}
+
+impl Default for MinorBreaks {
+    fn default() -> Self {
+        MinorBreaks::Between(1)
+    }
+}
+
+impl MinorBreaks {
+    // minor breaks inside `range`, leaving out those on a major break; the spacing of
+    // the major breaks is continued past the first and last one
+    pub fn compute(&self, major: &[f64], (min, max): (f64, f64)) -> Vec<f64> {
+        let minor = match self {
+            MinorBreaks::None => return Vec::new(),
+            MinorBreaks::Manual(breaks) => breaks.clone(),
+            MinorBreaks::Between(n) => {
+                if major.len() < 2 || *n == 0 {
+                    return Vec::new();
+                }
+                let step = major[1] - major[0];
+                let first = major[0] - step;
+                let last = major[major.len() - 1] + step;
+                let mut major = major.to_vec();
+                major.insert(0, first);
+                major.push(last);
+
+                major
+                    .windows(2)
+                    .flat_map(|pair| {
+                        let spacing = (pair[1] - pair[0]) / (*n + 1) as f64;
+                        (1..=*n).map(move |index| pair[0] + spacing * index as f64)
+                    })
+                    .collect()
+            }
+        };
+        minor
+            .into_iter()
+            .filter(|&value| value >= min && value <= max)
+            .filter(|value| !major.iter().any(|major| (major - value).abs() < EPSILON))
+            .collect()
+    }
+}
+
+// a round step (1, 2 or 5 times a power of ten) giving about `n` intervals over `span`
+pub fn nice_step(span: f64, n: usize) -> f64 {
+    let rough_step = span / n.max(1) as f64;
+    if !(rough_step.is_finite() && rough_step > 0.0) {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(rough_step.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|&step| step >= rough_step)
+        .unwrap_or(rough_step)
+}
+
+// multiples of the nice step inside the domain
+pub fn nice_breaks(min: f64, max: f64, n: usize) -> Vec<f64> {
+    width_breaks(min, max, nice_step(max - min, n))
+}
+
+pub fn width_breaks(min: f64, max: f64, width: f64) -> Vec<f64> {
+    if !(width.is_finite() && width > 0.0 && min.is_finite() && max.is_finite()) {
+        return Vec::new();
+    }
+    let first = (min / width - EPSILON).ceil() as i64;
+    let last = (max / width + EPSILON).floor() as i64;
+    (first..=last)
+        .take(MAX_BREAKS)
+        .map(|index| index as f64 * width)
+        .collect()
+}
+
+// the domain rounded outwards to multiples of its nice step
+pub fn nice_domain((min, max): (f64, f64)) -> (f64, f64) {
+    let step = nice_step(max - min, 5);
+    ((min / step).floor() * step, (max / step).ceil() * step)
+}
+
+fn simplicity(q_index: usize, j: f64, lmin: f64, lmax: f64, lstep: f64) -> f64 {
+    let n = Q.len() as f64;
+    let remainder = lmin.rem_euclid(lstep);
+    // bonus for including zero
+    let has_zero =
+        (remainder < EPSILON || lstep - remainder < EPSILON) && lmin <= 0.0 && lmax >= 0.0;
+    1.0 - q_index as f64 / (n - 1.0) - j + if has_zero { 1.0 } else { 0.0 }
+}
+
+fn simplicity_max(q_index: usize, j: f64) -> f64 {
+    let n = Q.len() as f64;
+    1.0 - q_index as f64 / (n - 1.0) - j + 1.0
+}
+
+fn coverage(dmin: f64, dmax: f64, lmin: f64, lmax: f64) -> f64 {
+    let range = dmax - dmin;
+    1.0 - 0.5 * ((dmax - lmax).powi(2) + (dmin - lmin).powi(2)) / (0.1 * range).powi(2)
+}
+
+fn coverage_max(dmin: f64, dmax: f64, span: f64) -> f64 {
+    let range = dmax - dmin;
+    if span > range {
+        let half = (span - range) / 2.0;
+        1.0 - 0.5 * (2.0 * half.powi(2)) / (0.1 * range).powi(2)
+    } else {
+        1.0
+    }
+}
+
+fn density(k: f64, m: f64, dmin: f64, dmax: f64, lmin: f64, lmax: f64) -> f64 {
+    let r = (k - 1.0) / (lmax - lmin);
+    let rt = (m - 1.0) / (lmax.max(dmax) - dmin.min(lmin));
+    2.0 - (r / rt).max(rt / r)
+}
+
+fn density_max(k: f64, m: f64) -> f64 {
+    if k >= m {
+        2.0 - (k - 1.0) / (m - 1.0)
+    } else {
+        1.0
+    }
+}
+
+// about `m` breaks covering [dmin, dmax], possibly reaching a little beyond it
+pub fn extended_breaks(dmin: f64, dmax: f64, m: usize) -> Vec<f64> {
+    // the search never ends on an infinite span
+    if !(dmax - dmin).is_finite() {
+        return Vec::new();
+    }
+    if dmax - dmin < EPSILON {
+        return vec![dmin];
+    }
+    // a span this small next to the values can't be stepped through in the search
+    // (`start + 1.0 == start`), e.g. a microsecond in epoch nanoseconds
+    if (dmax - dmin) / dmax.abs().max(dmin.abs()) < f64::EPSILON * 1e6 {
+        return nice_breaks(dmin, dmax, m);
+    }
+    let m = m.max(2) as f64;
+    let [w_simplicity, w_coverage, w_density, w_legibility] = WEIGHTS;
+    let mut best_score = -2.0;
+    let mut best: Option<(f64, f64, f64)> = None;
+
+    let mut j = 1.0;
+    'search: loop {
+        for (q_index, &q) in Q.iter().enumerate() {
+            let sm = simplicity_max(q_index, j);
+            if w_simplicity * sm + w_coverage + w_density + w_legibility < best_score {
+                break 'search;
+            }
+
+            let mut k = 2.0;
+            loop {
+                let dm = density_max(k, m);
+                if w_simplicity * sm + w_coverage + w_density * dm + w_legibility < best_score {
+                    break;
+                }
+
+                let delta = (dmax - dmin) / (k + 1.0) / j / q;
+                let mut z = delta.log10().ceil();
+                loop {
+                    let step = j * q * 10f64.powf(z);
+                    let cm = coverage_max(dmin, dmax, step * (k - 1.0));
+                    if w_simplicity * sm + w_coverage * cm + w_density * dm + w_legibility
+                        < best_score
+                    {
+                        break;
+                    }
+
+                    let min_start = (dmax / step).floor() * j - (k - 1.0) * j;
+                    let max_start = (dmin / step).ceil() * j;
+                    let mut start = min_start;
+                    while start <= max_start {
+                        let lmin = start * (step / j);
+                        let lmax = lmin + step * (k - 1.0);
+                        let score = w_simplicity * simplicity(q_index, j, lmin, lmax, step)
+                            + w_coverage * coverage(dmin, dmax, lmin, lmax)
+                            + w_density * density(k, m, dmin, dmax, lmin, lmax)
+                            + w_legibility;
+                        if score > best_score {
+                            best_score = score;
+                            best = Some((lmin, lmax, step));
+                        }
+                        start += 1.0;
+                    }
+                    z += 1.0;
+                }
+                k += 1.0;
+            }
+        }
+        j += 1.0;
+    }
+
+    match best {
+        None => Vec::new(),
+        Some((lmin, lmax, step)) => {
+            let n = ((lmax - lmin) / step).round() as i64;
+            (0..=n).map(|index| lmin + index as f64 * step).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rounded(breaks: Vec<f64>) -> Vec<f64> {
+        breaks
+            .into_iter()
+            .map(|value| (value * 1e9).round() / 1e9)
+            .collect()
+    }
+
+    #[test]
+    fn test_extended_breaks() {
+        assert_eq!(
+            extended_breaks(0.0, 10.0, 5),
+            vec![0.0, 2.5, 5.0, 7.5, 10.0]
+        );
+        assert_eq!(
+            extended_breaks(1.0, 99.0, 5),
+            vec![0.0, 25.0, 50.0, 75.0, 100.0]
+        );
+        assert_eq!(
+            rounded(extended_breaks(-0.37, 0.81, 5)),
+            vec![-0.4, -0.2, 0.0, 0.2, 0.4, 0.6, 0.8]
+        );
+        assert_eq!(extended_breaks(3.0, 3.0, 5), vec![3.0]);
+        assert!(extended_breaks(-f64::MAX, f64::MAX, 5).is_empty());
+        // tiny spans far from zero used to hang the search
+        for (min, max) in [(1e17, 1e17 + 16.0), (1.7e18, 1.7e18 + 1024.0)] {
+            let breaks = extended_breaks(min, max, 5);
+            assert!(!breaks.is_empty() && breaks.len() <= 8, "{:?}", breaks);
+        }
+
+        // always a handful of ticks, whatever the scale of the data
+        for (min, max) in [
+            (0.001, 0.0093),
+            (-1234.0, 98765.0),
+            (1e9, 1.3e9),
+            (17.0, 18.0),
+        ] {
+            let breaks = extended_breaks(min, max, 5);
+            assert!((3..=8).contains(&breaks.len()), "{:?}", breaks);
+        }
+    }
+
+    #[test]
+    fn test_nice_and_width_breaks() {
+        assert_eq!(
+            nice_breaks(0.0, 10.0, 5),
+            vec![0.0, 2.0, 4.0, 6.0, 8.0, 10.0]
+        );
+        assert_eq!(nice_breaks(-0.3, 1.2, 5), vec![0.0, 0.5, 1.0]);
+        assert_eq!(width_breaks(-1.0, 7.0, 3.0), vec![0.0, 3.0, 6.0]);
+        assert_eq!(width_breaks(1e6, 1.0000001e6, 1e-9).len(), MAX_BREAKS);
+        assert_eq!(nice_domain((0.3, 9.2)), (0.0, 10.0));
+    }
+
+    #[test]
+    fn test_minor_breaks() {
+        let major = [0.0, 2.0, 4.0];
+        assert_eq!(
+            MinorBreaks::default().compute(&major, (-1.5, 5.5)),
+            vec![-1.0, 1.0, 3.0, 5.0]
+        );
+        assert_eq!(
+            MinorBreaks::Between(3).compute(&major, (0.0, 2.0)),
+            vec![0.5, 1.0, 1.5]
+        );
+        assert_eq!(
+            MinorBreaks::Manual(vec![0.0, 0.5, 9.0]).compute(&major, (0.0, 4.0)),
+            vec![0.5]
+        );
+    }
+}
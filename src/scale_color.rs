@@ -0,0 +1,349 @@
+// Colour scales for continuous data. A GradientScale maps numbers onto a palette through
+// a continuous scale, so limits, transformations, breaks and labels work as they do for
+// positions. The palette is a two-colour gradient, a three-colour diverging one centred
+// on a midpoint, or any list of colours spread evenly over the domain. Binned scales cut
+// the domain at their breaks and give every bin a single colour. Missing values, and
+// values outside the limits unless squished, get the NA colour.
+//
+// A plot holds a ColorScale for each colour aesthetic: a gradient for continuous values,
+// a DiscreteScale (see scale_discrete.rs) for categorical ones, or a manual or identity
+// scale (see scale_manual.rs) for colours picked by hand or already in the data.
+
+use crate::primitives::Color;
+use crate::scale_breaks::Breaks;
+use crate::scale_discrete::DiscreteScale;
+use crate::scale_labels::LabelFormat;
+use crate::scale_manual::{IdentityScale, ManualScale};
+use crate::scale_transform::ScaleTransform;
+use crate::scales::{ContinuousScale, Expansion, OutOfBounds, Scale};
+
+// the default gradient, dark to light blue
+const LOW: Color = Color::rgb(0x13, 0x2B, 0x43);
+const HIGH: Color = Color::rgb(0x56, 0xB1, 0xF7);
+
+const VIRIDIS: [Color; 5] = [
+    Color::rgb(0x44, 0x01, 0x54),
+    Color::rgb(0x3B, 0x52, 0x8B),
+    Color::rgb(0x21, 0x90, 0x8C),
+    Color::rgb(0x5D, 0xC8, 0x63),
+    Color::rgb(0xFD, 0xE7, 0x25),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientScale {
+    // the domain, limits, transformation, breaks and labels; maps onto 0..1
+    continuous: ContinuousScale,
+    // spread evenly from the low end of the domain to the high end
+    colors: Vec<Color>,
+    // for diverging scales, the value the middle colour is at
+    midpoint: Option<f64>,
+    binned: bool,
+    na_color: Color,
+}
+
+impl Default for GradientScale {
+    fn default() -> Self {
+        GradientScale::new()
+    }
+}
+
+impl GradientScale {
+    pub fn new() -> Self {
+        GradientScale::colormap([LOW, HIGH])
+    }
+
+    pub fn gradient(low: Color, high: Color) -> Self {
+        GradientScale::colormap([low, high])
+    }
+
+    // `mid` at the midpoint (zero unless set), `low` and `high` as far either side of it
+    // as the further end of the domain
+    pub fn diverging(low: Color, mid: Color, high: Color) -> Self {
+        GradientScale {
+            midpoint: Some(0.0),
+            ..GradientScale::colormap([low, mid, high])
+        }
+    }
+
+    pub fn colormap(colors: impl IntoIterator<Item = Color>) -> Self {
+        GradientScale {
+            continuous: ContinuousScale::new().expand(Expansion::none()),
+            colors: colors.into_iter().collect(),
+            midpoint: None,
+            binned: false,
+            na_color: Color::NA,
+        }
+    }
+
+    // dark purple through blue and green to yellow, readable in greyscale
+    pub fn viridis() -> Self {
+        GradientScale::colormap(VIRIDIS)
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.continuous = self.continuous.name(name);
+        self
+    }
+
+    pub fn limits(mut self, min: f64, max: f64) -> Self {
+        self.continuous = self.continuous.limits(min, max);
+        self
+    }
+
+    pub fn transform(mut self, transform: ScaleTransform) -> Self {
+        self.continuous = self.continuous.transform(transform);
+        self
+    }
+
+    pub fn oob(mut self, oob: OutOfBounds) -> Self {
+        self.continuous = self.continuous.oob(oob);
+        self
+    }
+
+    pub fn breaks(mut self, breaks: Breaks) -> Self {
+        self.continuous = self.continuous.breaks(breaks);
+        self
+    }
+
+    pub fn labels(mut self, labels: LabelFormat) -> Self {
+        self.continuous = self.continuous.labels(labels);
+        self
+    }
+
+    // only used by diverging scales
+    pub fn midpoint(mut self, midpoint: f64) -> Self {
+        self.midpoint = Some(midpoint);
+        self
+    }
+
+    // one colour per bin between neighbouring breaks
+    pub fn binned(mut self) -> Self {
+        self.binned = true;
+        self
+    }
+
+    pub fn na_color(mut self, color: Color) -> Self {
+        self.na_color = color;
+        self
+    }
+
+    pub fn train(&mut self, values: &[f64]) {
+        self.continuous.train(values);
+    }
+
+    pub fn get_limits(&self) -> Option<(f64, f64)> {
+        self.continuous.get_limits()
+    }
+
+    pub fn get_breaks(&self) -> Vec<f64> {
+        self.continuous.get_breaks()
+    }
+
+    pub fn get_labels(&self) -> Vec<String> {
+        self.continuous.get_labels()
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.continuous.get_name()
+    }
+
+    pub fn map_color(&self, value: f64) -> Color {
+        self.map_color_in(value, &self.bin_edges())
+    }
+
+    // the bin edges are found once for all the values
+    pub fn map_colors(&self, values: &[f64]) -> Vec<Color> {
+        let edges = self.bin_edges();
+        values
+            .iter()
+            .map(|&value| self.map_color_in(value, &edges))
+            .collect()
+    }
+
+    // `n` evenly spaced values across the limits with their colours, for a colour bar
+    pub fn colorbar(&self, n: usize) -> Vec<(f64, Color)> {
+        (0..n)
+            .map(|index| {
+                let position = match n {
+                    1 => 0.5,
+                    _ => index as f64 / (n - 1) as f64,
+                };
+                let value = self.continuous.inverse(position);
+                (value, self.map_color(value))
+            })
+            .collect()
+    }
+
+    pub(crate) fn untrained(&self) -> Self {
+        GradientScale {
+            continuous: self.continuous.untrained(),
+            ..self.clone()
+        }
+    }
+
+    // where in the palette a position along the domain (0 to 1) falls; diverging scales
+    // put the midpoint in the middle
+    fn palette_position(&self, position: f64) -> f64 {
+        let Some(midpoint) = self.midpoint else {
+            return position;
+        };
+        let (min, max) = self.continuous.dimension();
+        let value = min + position * (max - min);
+        let midpoint = Scale::transform(&self.continuous, midpoint);
+        let reach = (min - midpoint).abs().max((max - midpoint).abs());
+        match reach > 0.0 {
+            true => 0.5 + (value - midpoint) / (2.0 * reach),
+            false => 0.5,
+        }
+    }
+
+    fn map_color_in(&self, value: f64, edges: &[f64]) -> Color {
+        let position = self.continuous.map(value);
+        if !position.is_finite() {
+            return self.na_color;
+        }
+        let position = match self.binned {
+            true => bin_center(position, edges),
+            false => position,
+        };
+        sample(&self.colors, self.palette_position(position)).unwrap_or(self.na_color)
+    }
+
+    // positions (0 to 1) of the edges between bins, empty unless binned
+    fn bin_edges(&self) -> Vec<f64> {
+        if !self.binned {
+            return Vec::new();
+        }
+        let mut edges = vec![0.0];
+        edges.extend(
+            self.get_breaks()
+                .into_iter()
+                .map(|value| self.continuous.map(value))
+                .filter(|&edge| edge > 1e-10 && edge < 1.0 - 1e-10),
+        );
+        edges.push(1.0);
+        edges
+    }
+}
+
+// the middle of the bin between edges a position falls in
+fn bin_center(position: f64, edges: &[f64]) -> f64 {
+    let bin = edges[1..]
+        .iter()
+        .position(|&edge| position < edge)
+        .unwrap_or(edges.len() - 2);
+    (edges[bin] + edges[bin + 1]) / 2.0
+}
+
+// the colour at `position` (0 to 1) along evenly spaced colours, mixed linearly
+fn sample(colors: &[Color], position: f64) -> Option<Color> {
+    let last = colors.len().checked_sub(1)?;
+    if last == 0 {
+        return Some(colors[0]);
+    }
+    let scaled = position.clamp(0.0, 1.0) * last as f64;
+    let index = (scaled.floor() as usize).min(last - 1);
+    Some(mix(colors[index], colors[index + 1], scaled - index as f64))
+}
+
+fn mix(low: Color, high: Color, t: f64) -> Color {
+    let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    Color {
+        r: channel(low.r, high.r),
+        g: channel(low.g, high.g),
+        b: channel(low.b, high.b),
+        a: channel(low.a, high.a),
+    }
+}
+
+// the scale for a colour aesthetic
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorScale {
+    Gradient(GradientScale),
+    Discrete(DiscreteScale),
+    Manual(ManualScale<Color>),
+    Identity(IdentityScale),
+}
+
+impl From<GradientScale> for ColorScale {
+    fn from(scale: GradientScale) -> Self {
+        ColorScale::Gradient(scale)
+    }
+}
+
+impl From<DiscreteScale> for ColorScale {
+    fn from(scale: DiscreteScale) -> Self {
+        ColorScale::Discrete(scale)
+    }
+}
+
+impl From<ManualScale<Color>> for ColorScale {
+    fn from(scale: ManualScale<Color>) -> Self {
+        ColorScale::Manual(scale)
+    }
+}
+
+impl From<IdentityScale> for ColorScale {
+    fn from(scale: IdentityScale) -> Self {
+        ColorScale::Identity(scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Color = Color::rgb(0, 0, 0);
+    const WHITE: Color = Color::rgb(255, 255, 255);
+
+    #[test]
+    fn test_gradients_and_colormaps() {
+        let mut scale = GradientScale::gradient(BLACK, WHITE);
+        scale.train(&[0.0, 10.0]);
+        assert_eq!(scale.map_color(0.0), BLACK);
+        assert_eq!(scale.map_color(5.0), Color::rgb(128, 128, 128));
+        assert_eq!(scale.map_color(10.0), WHITE);
+        assert_eq!(scale.map_color(f64::NAN), Color::NA);
+
+        let red = Color::rgb(255, 0, 0);
+        let mut colormap = GradientScale::colormap([BLACK, red, WHITE]);
+        colormap.train(&[0.0, 1.0]);
+        assert_eq!(colormap.map_color(0.5), red);
+        assert_eq!(colormap.map_color(0.75), Color::rgb(255, 128, 128));
+
+        // limits censor unless told to squish
+        let limited = GradientScale::gradient(BLACK, WHITE).limits(0.0, 1.0);
+        assert_eq!(limited.map_color(2.0), Color::NA);
+        assert_eq!(limited.oob(OutOfBounds::Squish).map_color(2.0), WHITE);
+
+        let bar = scale.colorbar(3);
+        assert_eq!(bar[1], (5.0, Color::rgb(128, 128, 128)));
+    }
+
+    #[test]
+    fn test_diverging_scales_centre_on_the_midpoint() {
+        let (blue, red) = (Color::rgb(0, 0, 255), Color::rgb(255, 0, 0));
+        let mut scale = GradientScale::diverging(blue, WHITE, red);
+        scale.train(&[-2.0, 8.0]);
+        assert_eq!(scale.map_color(0.0), WHITE);
+        assert_eq!(scale.map_color(8.0), red);
+        // -2 is a quarter of the way to the far end
+        assert_eq!(scale.map_color(-2.0), Color::rgb(191, 191, 255));
+
+        let shifted = scale.midpoint(8.0);
+        assert_eq!(shifted.map_color(8.0), WHITE);
+    }
+
+    #[test]
+    fn test_binned_scales_step_between_breaks() {
+        let mut scale = GradientScale::gradient(BLACK, WHITE)
+            .breaks(Breaks::Manual(vec![5.0]))
+            .binned();
+        scale.train(&[0.0, 10.0]);
+        // two bins, coloured as at their centres 2.5 and 7.5
+        assert_eq!(scale.map_color(0.0), Color::rgb(64, 64, 64));
+        assert_eq!(scale.map_color(4.9), Color::rgb(64, 64, 64));
+        assert_eq!(scale.map_color(5.0), Color::rgb(191, 191, 191));
+        assert_eq!(scale.map_color(10.0), Color::rgb(191, 191, 191));
+    }
+}
@@ -0,0 +1,465 @@
+// Scales for categorical data. Every distinct value is a level, in sorted order (see
+// DiscreteValue) unless the levels are given explicitly. As a position scale the levels
+// sit at 1, 2, ..., n and the Scale trait works with those numbers, so geoms and
+// coordinate systems treat them like any other position; numbers trained on the scale,
+// such as the edges of bars around a level, widen the domain the same way. For other
+// aesthetics the levels pick palette colours, marker shapes or line types.
+//
+// Values that aren't a level, e.g. because explicit levels leave them out, are missing:
+// they get no position, the NA colour and no shape or line type.
+
+use std::collections::BTreeSet;
+
+use crate::aesthetics::AestheticValue;
+use crate::data_groups::DiscreteValue;
+use crate::primitives::Color;
+use crate::scale_manual::{IdentityScale, ManualScale};
+use crate::scales::{Expansion, Scale};
+
+pub const SHAPES: [&str; 6] = ["circle", "triangle", "square", "plus", "cross", "diamond"];
+
+pub const LINETYPES: [&str; 6] = [
+    "solid", "dashed", "dotted", "dotdash", "longdash", "twodash",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscreteScale {
+    name: Option<String>,
+    // levels seen in training
+    trained: BTreeSet<DiscreteValue>,
+    // numbers seen in training, in position units
+    continuous: Option<(f64, f64)>,
+    // explicit levels, in this order
+    levels: Option<Vec<DiscreteValue>>,
+    // leave out explicit levels the data doesn't have
+    drop: bool,
+    expand: Expansion,
+    range: (f64, f64),
+    // the share of the space between neighbouring levels a band (a bar, say) takes up
+    band: f64,
+    // replace the level names on the axis, in level order
+    labels: Option<Vec<String>>,
+    // None picks evenly spaced hues
+    palette: Option<Vec<Color>>,
+    shapes: Vec<String>,
+    linetypes: Vec<String>,
+    na_color: Color,
+}
+
+impl Default for DiscreteScale {
+    fn default() -> Self {
+        DiscreteScale::new()
+    }
+}
+
+impl DiscreteScale {
+    pub fn new() -> Self {
+        DiscreteScale {
+            name: None,
+            trained: BTreeSet::new(),
+            continuous: None,
+            levels: None,
+            drop: false,
+            expand: Expansion::new(0.0, 0.6),
+            range: (0.0, 1.0),
+            band: 0.9,
+            labels: None,
+            palette: None,
+            shapes: SHAPES.iter().map(|shape| shape.to_string()).collect(),
+            linetypes: LINETYPES
+                .iter()
+                .map(|linetype| linetype.to_string())
+                .collect(),
+            na_color: Color::NA,
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    // these levels in this order instead of the sorted levels of the data
+    pub fn levels(mut self, levels: impl IntoIterator<Item = impl Into<DiscreteValue>>) -> Self {
+        self.levels = Some(levels.into_iter().map(Into::into).collect());
+        self
+    }
+
+    // whether explicit levels missing from the data are left out
+    pub fn drop(mut self, drop: bool) -> Self {
+        self.drop = drop;
+        self
+    }
+
+    // in position units, where neighbouring levels are 1 apart
+    pub fn expand(mut self, expand: Expansion) -> Self {
+        self.expand = expand;
+        self
+    }
+
+    pub fn range(mut self, start: f64, end: f64) -> Self {
+        self.range = (start, end);
+        self
+    }
+
+    pub fn band(mut self, band: f64) -> Self {
+        self.band = band.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn labels(mut self, labels: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.labels = Some(labels.into_iter().map(Into::into).collect());
+        self
+    }
+
+    // colours for the levels in order, repeated if there are more levels
+    pub fn palette(mut self, palette: impl IntoIterator<Item = Color>) -> Self {
+        self.palette = Some(palette.into_iter().collect());
+        self
+    }
+
+    pub fn shapes(mut self, shapes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.shapes = shapes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn linetypes(mut self, linetypes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.linetypes = linetypes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    // the colour of missing and unknown values
+    pub fn na_color(mut self, color: Color) -> Self {
+        self.na_color = color;
+        self
+    }
+
+    // add these values' levels; missing values aren't a level
+    pub fn train_discrete(&mut self, values: &[DiscreteValue]) {
+        let present = values
+            .iter()
+            .filter(|value| !matches!(value, DiscreteValue::Missing));
+        self.trained.extend(present.cloned());
+    }
+
+    pub fn get_levels(&self) -> Vec<DiscreteValue> {
+        self.level_iter().cloned().collect()
+    }
+
+    fn level_iter(&self) -> Box<dyn Iterator<Item = &DiscreteValue> + '_> {
+        match &self.levels {
+            None => Box::new(self.trained.iter()),
+            Some(levels) => Box::new(
+                levels
+                    .iter()
+                    .filter(|level| !self.drop || self.trained.contains(level)),
+            ),
+        }
+    }
+
+    fn n_levels(&self) -> usize {
+        self.level_iter().count()
+    }
+
+    // the level's place in the order, None for values that aren't a level
+    pub fn index(&self, value: &DiscreteValue) -> Option<usize> {
+        self.level_iter().position(|level| level == value)
+    }
+
+    // the position the scale maps from, NaN for values that aren't a level
+    pub fn position(&self, value: &DiscreteValue) -> f64 {
+        self.index(value)
+            .map_or(f64::NAN, |index| (index + 1) as f64)
+    }
+
+    pub fn map_color(&self, value: &DiscreteValue) -> Color {
+        let Some(index) = self.index(value) else {
+            return self.na_color;
+        };
+        match &self.palette {
+            Some(palette) if !palette.is_empty() => palette[index % palette.len()],
+            Some(_) => self.na_color,
+            None => hue(15.0 + 360.0 * index as f64 / self.n_levels() as f64),
+        }
+    }
+
+    pub fn map_shape(&self, value: &DiscreteValue) -> Option<&str> {
+        cycled(&self.shapes, self.index(value)?)
+    }
+
+    pub fn map_linetype(&self, value: &DiscreteValue) -> Option<&str> {
+        cycled(&self.linetypes, self.index(value)?)
+    }
+
+    // the width of a band around a level in output units, e.g. for bars
+    pub fn band_width(&self) -> f64 {
+        let (min, max) = self.dimension();
+        let (start, end) = self.range;
+        self.band * (end - start).abs() / (max - min)
+    }
+
+    fn untrained(&self) -> Self {
+        DiscreteScale {
+            trained: BTreeSet::new(),
+            continuous: None,
+            ..self.clone()
+        }
+    }
+}
+
+impl Scale for DiscreteScale {
+    // positions around the levels, e.g. the edges of bars
+    fn train(&mut self, values: &[f64]) {
+        for &value in values.iter().filter(|value| value.is_finite()) {
+            self.continuous = Some(match self.continuous {
+                None => (value, value),
+                Some((min, max)) => (min.min(value), max.max(value)),
+            });
+        }
+    }
+
+    // the first and last level's positions, widened by any numbers trained
+    fn get_limits(&self) -> Option<(f64, f64)> {
+        let levels: Option<(f64, f64)> = match self.n_levels() {
+            0 => None,
+            n => Some((1.0, n as f64)),
+        };
+        match (levels, self.continuous) {
+            (Some((min, max)), Some((low, high))) => Some((min.min(low), max.max(high))),
+            (levels, continuous) => levels.or(continuous),
+        }
+    }
+
+    fn dimension(&self) -> (f64, f64) {
+        match self.get_limits().map(|limits| self.expand.apply(limits)) {
+            None => (0.0, 1.0),
+            Some((min, max)) if min == max => (min - 0.5, max + 0.5),
+            Some(dimension) => dimension,
+        }
+    }
+
+    fn map(&self, value: f64) -> f64 {
+        let (min, max) = self.dimension();
+        let (start, end) = self.range;
+        start + (value - min) / (max - min) * (end - start)
+    }
+
+    fn inverse(&self, value: f64) -> f64 {
+        let (min, max) = self.dimension();
+        let (start, end) = self.range;
+        min + (value - start) / (end - start) * (max - min)
+    }
+
+    fn get_breaks(&self) -> Vec<f64> {
+        (1..=self.n_levels())
+            .map(|position| position as f64)
+            .collect()
+    }
+
+    fn get_labels(&self) -> Vec<String> {
+        self.level_iter()
+            .enumerate()
+            .map(|(index, level)| {
+                let label = self.labels.as_ref().and_then(|labels| labels.get(index));
+                label.cloned().unwrap_or_else(|| level.to_string())
+            })
+            .collect()
+    }
+
+    fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn box_clone(&self) -> Box<dyn Scale> {
+        Box::new(self.untrained())
+    }
+
+    fn as_discrete(&self) -> Option<&DiscreteScale> {
+        Some(self)
+    }
+
+    fn as_discrete_mut(&mut self) -> Option<&mut DiscreteScale> {
+        Some(self)
+    }
+}
+
+fn cycled(values: &[String], index: usize) -> Option<&str> {
+    match values.is_empty() {
+        true => None,
+        false => Some(&values[index % values.len()]),
+    }
+}
+
+// a medium-light colour of the given hue in degrees
+fn hue(degrees: f64) -> Color {
+    let (saturation, lightness) = (0.65, 0.6);
+    let chroma = (1.0 - (2.0 * lightness - 1.0f64).abs()) * saturation;
+    let sector = (degrees % 360.0) / 60.0;
+    let second = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, second, 0.0),
+        1 => (second, chroma, 0.0),
+        2 => (0.0, chroma, second),
+        3 => (0.0, second, chroma),
+        4 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second),
+    };
+    let base = lightness - chroma / 2.0;
+    let channel = |value: f64| ((value + base) * 255.0).round() as u8;
+    Color::rgb(channel(r), channel(g), channel(b))
+}
+
+// the scale for a shape or line type aesthetic, whose outputs are names
+#[derive(Debug, Clone, PartialEq)]
+pub enum NameScale {
+    Discrete(DiscreteScale),
+    Manual(ManualScale<String>),
+    Identity(IdentityScale),
+}
+
+impl NameScale {
+    // None for values without a shape: missing ones, those a manual scale has no output
+    // for and identity values that aren't text
+    pub fn map_shape<'a>(&'a self, value: &'a AestheticValue) -> Option<&'a str> {
+        match self {
+            NameScale::Discrete(scale) => scale.map_shape(&DiscreteValue::from(value)),
+            _ => self.map_name(value),
+        }
+    }
+
+    pub fn map_linetype<'a>(&'a self, value: &'a AestheticValue) -> Option<&'a str> {
+        match self {
+            NameScale::Discrete(scale) => scale.map_linetype(&DiscreteValue::from(value)),
+            _ => self.map_name(value),
+        }
+    }
+
+    fn map_name<'a>(&'a self, value: &'a AestheticValue) -> Option<&'a str> {
+        match self {
+            NameScale::Discrete(_) => None,
+            NameScale::Manual(scale) => scale.map(&DiscreteValue::from(value)).map(String::as_str),
+            NameScale::Identity(scale) => scale.map_name(value),
+        }
+    }
+
+    // discrete scales learn the levels, the others have nothing to learn
+    pub fn train(&mut self, values: &[AestheticValue]) {
+        if let NameScale::Discrete(scale) = self {
+            let levels: Vec<DiscreteValue> = values.iter().map(DiscreteValue::from).collect();
+            scale.train_discrete(&levels);
+        }
+    }
+}
+
+impl From<DiscreteScale> for NameScale {
+    fn from(scale: DiscreteScale) -> Self {
+        NameScale::Discrete(scale)
+    }
+}
+
+impl From<ManualScale<String>> for NameScale {
+    fn from(scale: ManualScale<String>) -> Self {
+        NameScale::Manual(scale)
+    }
+}
+
+impl From<IdentityScale> for NameScale {
+    fn from(scale: IdentityScale) -> Self {
+        NameScale::Identity(scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(values: &[&str]) -> Vec<DiscreteValue> {
+        values
+            .iter()
+            .map(|&value| DiscreteValue::from(value))
+            .collect()
+    }
+
+    #[test]
+    fn test_levels_are_evenly_spaced_positions() {
+        let mut scale = DiscreteScale::new();
+        scale.train_discrete(&text(&["b", "a", "c", "a"]));
+        scale.train_discrete(&[DiscreteValue::Missing]);
+
+        assert_eq!(scale.get_levels(), text(&["a", "b", "c"]));
+        assert_eq!(scale.position(&"b".into()), 2.0);
+        assert!(scale.position(&"z".into()).is_nan());
+        assert_eq!(scale.get_breaks(), vec![1.0, 2.0, 3.0]);
+        assert_eq!(scale.get_labels(), vec!["a", "b", "c"]);
+
+        // 0.6 on each side, so a level's band never touches the panel edge
+        assert_eq!(scale.dimension(), (0.4, 3.6));
+        assert!((scale.map(2.0) - 0.5).abs() < 1e-12);
+        assert!((scale.band_width() - 0.9 / 3.2).abs() < 1e-12);
+
+        // numbers widen the domain around the levels
+        scale.train(&[0.5, 3.5]);
+        assert_eq!(scale.get_limits(), Some((0.5, 3.5)));
+        assert_eq!(scale.box_clone().get_limits(), None);
+    }
+
+    #[test]
+    fn test_explicit_levels_order_drop_and_unseen_values() {
+        let mut scale = DiscreteScale::new()
+            .levels(["high", "medium", "low"])
+            .labels(["High", "Medium"]);
+        scale.train_discrete(&text(&["low", "high", "other"]));
+
+        assert_eq!(scale.position(&"high".into()), 1.0);
+        assert_eq!(scale.position(&"low".into()), 3.0);
+        assert!(scale.position(&"other".into()).is_nan());
+        assert_eq!(scale.get_labels(), vec!["High", "Medium", "low"]);
+
+        let dropped = scale.drop(true);
+        assert_eq!(dropped.get_levels(), text(&["high", "low"]));
+        assert_eq!(dropped.position(&"low".into()), 2.0);
+    }
+
+    #[test]
+    fn test_palettes_shapes_and_linetypes() {
+        let red = Color::rgb(255, 0, 0);
+        let blue = Color::rgb(0, 0, 255);
+        let mut scale = DiscreteScale::new().palette([red, blue]);
+        scale.train_discrete(&text(&["a", "b", "c"]));
+
+        // palettes are repeated when there are more levels than colours
+        assert_eq!(scale.map_color(&"a".into()), red);
+        assert_eq!(scale.map_color(&"c".into()), red);
+        assert_eq!(scale.map_color(&DiscreteValue::Missing), Color::NA);
+        assert_eq!(scale.map_shape(&"b".into()), Some("triangle"));
+        assert_eq!(scale.map_linetype(&"c".into()), Some("dotted"));
+        assert_eq!(scale.map_shape(&"z".into()), None);
+
+        // hues by default, all different
+        let mut hues = DiscreteScale::new();
+        hues.train_discrete(&text(&["a", "b", "c"]));
+        let colours: BTreeSet<_> = ["a", "b", "c"]
+            .iter()
+            .map(|&level| format!("{:?}", hues.map_color(&level.into())))
+            .collect();
+        assert_eq!(colours.len(), 3);
+    }
+
+    #[test]
+    fn test_name_scales_map_shapes_and_linetypes() {
+        let text = |text: &str| AestheticValue::Text(text.to_string());
+
+        let mut discrete = NameScale::from(DiscreteScale::new());
+        discrete.train(&[text("b"), text("a")]);
+        assert_eq!(discrete.map_shape(&text("b")), Some("triangle"));
+        assert_eq!(discrete.map_linetype(&text("a")), Some("solid"));
+
+        let manual = NameScale::from(ManualScale::new().value("a", "dotted".to_string()));
+        assert_eq!(manual.map_linetype(&text("a")), Some("dotted"));
+        assert_eq!(manual.map_linetype(&text("b")), None);
+
+        let identity = NameScale::from(IdentityScale::new());
+        assert_eq!(identity.map_shape(&text("cross")), Some("cross"));
+        assert_eq!(identity.map_shape(&AestheticValue::Numeric(1.0)), None);
+    }
+}
@@ -0,0 +1,258 @@
+// Text for the breaks of a continuous scale. Formats work on all breaks at once so
+// that, e.g., automatic decimals are the same for every tick of an axis.
+
+use std::fmt;
+use std::sync::Arc;
+
+// the most decimals automatic formats will show
+const MAX_DECIMALS: usize = 10;
+
+const SI_PREFIXES: [(f64, &str); 10] = [
+    (1e15, "P"),
+    (1e12, "T"),
+    (1e9, "G"),
+    (1e6, "M"),
+    (1e3, "k"),
+    (1.0, ""),
+    (1e-3, "m"),
+    (1e-6, "µ"),
+    (1e-9, "n"),
+    (1e-12, "p"),
+];
+
+#[derive(Clone, Default)]
+pub enum LabelFormat {
+    // as few decimals as needed to show every break exactly
+    #[default]
+    Auto,
+    // this many decimals
+    Fixed(usize),
+    // digits grouped in thousands with this separator, e.g. 1,234,567
+    Thousands(char),
+    // multiplied by 100, with this many decimals and a % sign
+    Percent(usize),
+    // a prefix symbol, thousands separated, with this many decimals, e.g. -$1,234.50
+    Currency {
+        symbol: String,
+        decimals: usize,
+    },
+    // scaled by an SI prefix (k, M, G, m, µ, ...) with a unit after it
+    Si {
+        unit: String,
+    },
+    // mantissa with this many decimals and a power of ten, e.g. 1.50e3
+    Scientific(usize),
+    Custom(Arc<dyn Fn(f64) -> String + Send + Sync>),
+}
+
+impl fmt::Debug for LabelFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LabelFormat::Auto => write!(f, "Auto"),
+            LabelFormat::Fixed(decimals) => write!(f, "Fixed({})", decimals),
+            LabelFormat::Thousands(separator) => write!(f, "Thousands({:?})", separator),
+            LabelFormat::Percent(decimals) => write!(f, "Percent({})", decimals),
+            LabelFormat::Currency { symbol, decimals } => write!(
+                f,
+                "Currency {{ symbol: {:?}, decimals: {} }}",
+                symbol, decimals
+            ),
+            LabelFormat::Si { unit } => write!(f, "Si {{ unit: {:?} }}", unit),
+            LabelFormat::Scientific(decimals) => write!(f, "Scientific({})", decimals),
+            LabelFormat::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+impl PartialEq for LabelFormat {
+    fn eq(&self, other: &Self) -> bool {
+        use LabelFormat::*;
+        match (self, other) {
+            (Auto, Auto) => true,
+            (Fixed(a), Fixed(b)) | (Percent(a), Percent(b)) | (Scientific(a), Scientific(b)) => {
+                a == b
+            }
+            (Thousands(a), Thousands(b)) => a == b,
+            (
+                Currency { symbol, decimals },
+                Currency {
+                    symbol: other_symbol,
+                    decimals: other_decimals,
+                },
+            ) => symbol == other_symbol && decimals == other_decimals,
+            (Si { unit }, Si { unit: other_unit }) => unit == other_unit,
+            // closures can't be compared, only recognised as the same one
+            (Custom(a), Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl LabelFormat {
+    pub fn custom(format: impl Fn(f64) -> String + Send + Sync + 'static) -> Self {
+        LabelFormat::Custom(Arc::new(format))
+    }
+
+    pub fn format_all(&self, values: &[f64]) -> Vec<String> {
+        match self {
+            LabelFormat::Auto => {
+                let decimals = auto_decimals(values);
+                values.iter().map(|&value| fixed(value, decimals)).collect()
+            }
+            LabelFormat::Fixed(decimals) => values
+                .iter()
+                .map(|&value| fixed(value, *decimals))
+                .collect(),
+            LabelFormat::Thousands(separator) => {
+                let decimals = auto_decimals(values);
+                values
+                    .iter()
+                    .map(|&value| group_thousands(&fixed(value, decimals), *separator))
+                    .collect()
+            }
+            LabelFormat::Percent(decimals) => values
+                .iter()
+                .map(|&value| format!("{}%", fixed(value * 100.0, *decimals)))
+                .collect(),
+            LabelFormat::Currency { symbol, decimals } => values
+                .iter()
+                .map(|&value| {
+                    let amount = group_thousands(&fixed(value.abs(), *decimals), ',');
+                    match value < 0.0 {
+                        true => format!("-{}{}", symbol, amount),
+                        false => format!("{}{}", symbol, amount),
+                    }
+                })
+                .collect(),
+            LabelFormat::Si { unit } => values.iter().map(|&value| si(value, unit)).collect(),
+            LabelFormat::Scientific(decimals) => values
+                .iter()
+                .map(|&value| format!("{:.*e}", decimals, value))
+                .collect(),
+            LabelFormat::Custom(format) => values.iter().map(|&value| format(value)).collect(),
+        }
+    }
+}
+
+// the fewest decimals (up to MAX_DECIMALS) at which every value is shown exactly
+fn auto_decimals(values: &[f64]) -> usize {
+    (0..MAX_DECIMALS)
+        .find(|&decimals| {
+            let factor = 10f64.powi(decimals as i32);
+            values
+                .iter()
+                .filter(|value| value.is_finite())
+                .all(|value| {
+                    let scaled = value * factor;
+                    (scaled - scaled.round()).abs() < 1e-6 * scaled.abs().max(1.0)
+                })
+        })
+        .unwrap_or(MAX_DECIMALS)
+}
+
+fn fixed(value: f64, decimals: usize) -> String {
+    if !value.is_finite() {
+        return "NA".to_string();
+    }
+    let text = format!("{:.*}", decimals, value);
+    // no "-0" for values that round to zero
+    match text.strip_prefix('-') {
+        Some(rest) if rest.chars().all(|c| c == '0' || c == '.') => rest.to_string(),
+        _ => text,
+    }
+}
+
+fn group_thousands(number: &str, separator: char) -> String {
+    let (sign, digits) = match number.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", number),
+    };
+    let (integer, fraction) = match digits.find('.') {
+        Some(point) => digits.split_at(point),
+        None => (digits, ""),
+    };
+    let mut grouped = String::new();
+    for (index, digit) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    format!("{}{}{}", sign, grouped, fraction)
+}
+
+fn si(value: f64, unit: &str) -> String {
+    if !value.is_finite() {
+        return "NA".to_string();
+    }
+    let magnitude = value.abs();
+    let (factor, prefix) = match magnitude == 0.0 {
+        true => (1.0, ""),
+        false => SI_PREFIXES
+            .iter()
+            .find(|(factor, _)| magnitude >= factor * (1.0 - 1e-9))
+            .copied()
+            .unwrap_or(SI_PREFIXES[SI_PREFIXES.len() - 1]),
+    };
+    let scaled = value / factor;
+    format!(
+        "{}{}{}",
+        fixed(scaled, auto_decimals(&[scaled]).min(3)),
+        prefix,
+        unit
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_automatic_and_fixed_decimals() {
+        assert_eq!(
+            LabelFormat::Auto.format_all(&[0.0, 0.5, 1.0]),
+            vec!["0.0", "0.5", "1.0"]
+        );
+        assert_eq!(
+            LabelFormat::Auto.format_all(&[0.1 + 0.2, -1.0 + 1e-17]),
+            vec!["0.3", "-1.0"]
+        );
+        assert_eq!(LabelFormat::Auto.format_all(&[0.0, 25.0]), vec!["0", "25"]);
+        assert_eq!(LabelFormat::Fixed(2).format_all(&[-0.001]), vec!["0.00"]);
+    }
+
+    #[test]
+    fn test_number_styles() {
+        assert_eq!(
+            LabelFormat::Thousands(',').format_all(&[1234567.0, -1000.5]),
+            vec!["1,234,567.0", "-1,000.5"]
+        );
+        assert_eq!(
+            LabelFormat::Percent(0).format_all(&[0.25, 1.0]),
+            vec!["25%", "100%"]
+        );
+        let dollars = LabelFormat::Currency {
+            symbol: "$".to_string(),
+            decimals: 2,
+        };
+        assert_eq!(
+            dollars.format_all(&[-1234.5, 0.0]),
+            vec!["-$1,234.50", "$0.00"]
+        );
+        let si = LabelFormat::Si {
+            unit: "B".to_string(),
+        };
+        assert_eq!(
+            si.format_all(&[0.0, 1500.0, 2e6, 0.005]),
+            vec!["0B", "1.5kB", "2MB", "5mB"]
+        );
+        assert_eq!(
+            LabelFormat::Scientific(1).format_all(&[12600.0]),
+            vec!["1.3e4"]
+        );
+        assert_eq!(
+            LabelFormat::custom(|value| format!("{}h", value)).format_all(&[3.0]),
+            vec!["3h"]
+        );
+    }
+}
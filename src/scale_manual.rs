@@ -0,0 +1,173 @@
+// Scales that take their outputs from the user or the data instead of computing them.
+//
+// A ManualScale maps values to outputs given explicitly, e.g. "A" to red and "B" to blue,
+// and lists them in that order in a legend. Values it has no output for get its fallback,
+// or are missing without one. The outputs can be colours, numbers (sizes, alphas, line
+// widths) or names (shapes, line types).
+//
+// An IdentityScale uses the data as the output: numbers as they are, colours or colour
+// names and hex codes parsed from text, and text as shape or line type names. It isn't
+// trained and shows no legend.
+
+use crate::aesthetics::AestheticValue;
+use crate::data_groups::DiscreteValue;
+use crate::primitives::Color;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManualScale<T> {
+    name: Option<String>,
+    // in the order they were given
+    values: Vec<(DiscreteValue, T)>,
+    fallback: Option<T>,
+}
+
+impl<T> Default for ManualScale<T> {
+    fn default() -> Self {
+        ManualScale::new()
+    }
+}
+
+impl<T> ManualScale<T> {
+    pub fn new() -> Self {
+        ManualScale {
+            name: None,
+            values: Vec::new(),
+            fallback: None,
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    // replaces an earlier output for the same value
+    pub fn value(mut self, value: impl Into<DiscreteValue>, output: T) -> Self {
+        let value = value.into();
+        match self.values.iter_mut().find(|(known, _)| *known == value) {
+            Some((_, known)) => *known = output,
+            None => self.values.push((value, output)),
+        }
+        self
+    }
+
+    pub fn values<V: Into<DiscreteValue>>(self, values: impl IntoIterator<Item = (V, T)>) -> Self {
+        values
+            .into_iter()
+            .fold(self, |scale, (value, output)| scale.value(value, output))
+    }
+
+    // the output for values without one of their own
+    pub fn fallback(mut self, output: T) -> Self {
+        self.fallback = Some(output);
+        self
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn get_levels(&self) -> Vec<DiscreteValue> {
+        self.values.iter().map(|(value, _)| value.clone()).collect()
+    }
+
+    pub fn map(&self, value: &DiscreteValue) -> Option<&T> {
+        self.values
+            .iter()
+            .find(|(known, _)| known == value)
+            .map(|(_, output)| output)
+            .or(self.fallback.as_ref())
+    }
+
+    // the values with their labels and outputs, for a legend
+    pub fn legend(&self) -> Vec<(String, &T)> {
+        self.values
+            .iter()
+            .map(|(value, output)| (value.to_string(), output))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IdentityScale {
+    name: Option<String>,
+}
+
+impl IdentityScale {
+    pub fn new() -> Self {
+        IdentityScale::default()
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    // colours as they are, text parsed as a colour name or hex code
+    pub fn map_color(&self, value: &AestheticValue) -> Option<Color> {
+        match value {
+            AestheticValue::Color(color) => Some(*color),
+            AestheticValue::Text(text) => Color::parse(text),
+            _ => None,
+        }
+    }
+
+    pub fn map_number(&self, value: &AestheticValue) -> Option<f64> {
+        value.as_numeric().filter(|number| number.is_finite())
+    }
+
+    // shape and line type names
+    pub fn map_name<'a>(&self, value: &'a AestheticValue) -> Option<&'a str> {
+        match value {
+            AestheticValue::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_scales_map_values_with_a_fallback() {
+        let red = Color::rgb(255, 0, 0);
+        let blue = Color::rgb(0, 0, 255);
+        let colors = ManualScale::new().value("A", red).value("B", blue);
+        assert_eq!(colors.map(&"A".into()), Some(&red));
+        assert_eq!(colors.map(&"C".into()), None);
+        assert_eq!(
+            colors.clone().fallback(Color::NA).map(&"C".into()),
+            Some(&Color::NA)
+        );
+        assert_eq!(
+            colors.legend(),
+            vec![("A".to_string(), &red), ("B".to_string(), &blue)]
+        );
+
+        let sizes = ManualScale::new().values([(4.0, 2.0), (8.0, 6.0), (4.0, 3.0)]);
+        assert_eq!(sizes.get_levels(), vec![4.0.into(), 8.0.into()]);
+        assert_eq!(sizes.map(&4.0.into()), Some(&3.0));
+
+        let shapes = ManualScale::new().value(true, "square".to_string());
+        assert_eq!(shapes.map(&true.into()).map(String::as_str), Some("square"));
+    }
+
+    #[test]
+    fn test_identity_scales_pass_data_through() {
+        let scale = IdentityScale::new();
+        let text = |text: &str| AestheticValue::Text(text.to_string());
+        assert_eq!(
+            scale.map_color(&text("#ff0000")),
+            Some(Color::rgb(255, 0, 0))
+        );
+        assert_eq!(scale.map_color(&text("not a colour")), None);
+        assert_eq!(scale.map_number(&AestheticValue::Numeric(4.5)), Some(4.5));
+        assert_eq!(scale.map_number(&AestheticValue::Missing), None);
+        assert_eq!(scale.map_name(&text("dashed")), Some("dashed"));
+    }
+}
@@ -0,0 +1,253 @@
+// Scales for aesthetics drawn as a number: point size (a radius in pixels), alpha and line
+// width. Values go through a continuous scale, so limits, transformations, breaks and
+// labels work as they do for positions, and then land in an output range. Sizes change
+// in area by default, as the eye judges points by their area rather than their radius;
+// radius scales change the radius linearly instead.
+//
+// Missing values, and values censored by the limits, get the scale's NA value.
+//
+// A plot holds a NumberScale for each of these aesthetics: a range scale, or a manual or
+// identity scale (see scale_manual.rs) for numbers picked by hand or already in the data.
+
+use crate::aesthetics::AestheticValue;
+use crate::data_groups::DiscreteValue;
+use crate::scale_breaks::Breaks;
+use crate::scale_labels::LabelFormat;
+use crate::scale_manual::{IdentityScale, ManualScale};
+use crate::scale_transform::ScaleTransform;
+use crate::scales::{ContinuousScale, Expansion, OutOfBounds, Scale};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeScale {
+    // the domain, limits, transformation, breaks and labels; maps onto 0..1
+    continuous: ContinuousScale,
+    range: (f64, f64),
+    // interpolate the square of the output, i.e. the area of a point
+    area: bool,
+    na_value: f64,
+}
+
+impl RangeScale {
+    fn with_range(range: (f64, f64), area: bool, na_value: f64) -> Self {
+        RangeScale {
+            continuous: ContinuousScale::new().expand(Expansion::none()),
+            range,
+            area,
+            na_value,
+        }
+    }
+
+    // point radii from 1.5 to 6 pixels, the area growing linearly with the data
+    pub fn size() -> Self {
+        RangeScale::with_range((1.5, 6.0), true, 3.0)
+    }
+
+    // point radii from 1.5 to 6 pixels, growing linearly with the data
+    pub fn radius() -> Self {
+        RangeScale::with_range((1.5, 6.0), false, 3.0)
+    }
+
+    pub fn alpha() -> Self {
+        RangeScale::with_range((0.1, 1.0), false, 1.0)
+    }
+
+    // line widths from 0.5 to 3 pixels
+    pub fn linewidth() -> Self {
+        RangeScale::with_range((0.5, 3.0), false, 1.0)
+    }
+
+    // output for the low and high end of the domain
+    pub fn range(mut self, low: f64, high: f64) -> Self {
+        self.range = (low, high);
+        self
+    }
+
+    pub fn na_value(mut self, value: f64) -> Self {
+        self.na_value = value;
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.continuous = self.continuous.name(name);
+        self
+    }
+
+    pub fn limits(mut self, min: f64, max: f64) -> Self {
+        self.continuous = self.continuous.limits(min, max);
+        self
+    }
+
+    pub fn transform(mut self, transform: ScaleTransform) -> Self {
+        self.continuous = self.continuous.transform(transform);
+        self
+    }
+
+    pub fn oob(mut self, oob: OutOfBounds) -> Self {
+        self.continuous = self.continuous.oob(oob);
+        self
+    }
+
+    pub fn breaks(mut self, breaks: Breaks) -> Self {
+        self.continuous = self.continuous.breaks(breaks);
+        self
+    }
+
+    pub fn labels(mut self, labels: LabelFormat) -> Self {
+        self.continuous = self.continuous.labels(labels);
+        self
+    }
+
+    pub fn get_range(&self) -> (f64, f64) {
+        self.range
+    }
+
+    pub fn get_na_value(&self) -> f64 {
+        self.na_value
+    }
+
+    pub fn train(&mut self, values: &[f64]) {
+        self.continuous.train(values);
+    }
+
+    pub fn get_limits(&self) -> Option<(f64, f64)> {
+        self.continuous.get_limits()
+    }
+
+    pub fn get_breaks(&self) -> Vec<f64> {
+        self.continuous.get_breaks()
+    }
+
+    pub fn get_labels(&self) -> Vec<String> {
+        self.continuous.get_labels()
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.continuous.get_name()
+    }
+
+    pub fn map(&self, value: f64) -> f64 {
+        let position = self.continuous.map(value);
+        if !position.is_finite() {
+            return self.na_value;
+        }
+        let (low, high) = self.range;
+        match self.area {
+            true => (low * low + position * (high * high - low * low)).sqrt(),
+            false => low + position * (high - low),
+        }
+    }
+
+    pub fn map_all(&self, values: &[f64]) -> Vec<f64> {
+        values.iter().map(|&value| self.map(value)).collect()
+    }
+
+    // the breaks with their outputs and labels, for a legend
+    pub fn legend(&self) -> Vec<(f64, f64, String)> {
+        self.get_breaks()
+            .into_iter()
+            .zip(self.get_labels())
+            .map(|(value, label)| (value, self.map(value), label))
+            .collect()
+    }
+
+    pub(crate) fn untrained(&self) -> Self {
+        RangeScale {
+            continuous: self.continuous.untrained(),
+            ..self.clone()
+        }
+    }
+}
+
+// the scale for a size, alpha or line width aesthetic
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumberScale {
+    Range(Box<RangeScale>),
+    Manual(ManualScale<f64>),
+    Identity(IdentityScale),
+}
+
+impl NumberScale {
+    // None for values a manual scale has no output for and identity values that aren't
+    // numbers
+    pub fn map(&self, value: &AestheticValue) -> Option<f64> {
+        match self {
+            NumberScale::Range(scale) => Some(scale.map(value.as_numeric().unwrap_or(f64::NAN))),
+            NumberScale::Manual(scale) => scale.map(&DiscreteValue::from(value)).copied(),
+            NumberScale::Identity(scale) => scale.map_number(value),
+        }
+    }
+
+    // range scales are trained on the numbers, the others have nothing to learn
+    pub fn train(&mut self, values: &[AestheticValue]) {
+        if let NumberScale::Range(scale) = self {
+            let numbers: Vec<f64> = values
+                .iter()
+                .map(|value| value.as_numeric().unwrap_or(f64::NAN))
+                .collect();
+            scale.train(&numbers);
+        }
+    }
+
+    pub(crate) fn untrained(&self) -> Self {
+        match self {
+            NumberScale::Range(scale) => NumberScale::Range(Box::new(scale.untrained())),
+            other => other.clone(),
+        }
+    }
+}
+
+impl From<RangeScale> for NumberScale {
+    fn from(scale: RangeScale) -> Self {
+        NumberScale::Range(Box::new(scale))
+    }
+}
+
+impl From<ManualScale<f64>> for NumberScale {
+    fn from(scale: ManualScale<f64>) -> Self {
+        NumberScale::Manual(scale)
+    }
+}
+
+impl From<IdentityScale> for NumberScale {
+    fn from(scale: IdentityScale) -> Self {
+        NumberScale::Identity(scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_area_and_radius_sizes() {
+        let mut area = RangeScale::size().range(0.0, 10.0);
+        area.train(&[0.0, 100.0]);
+        // a quarter of the data, a quarter of the area, half the radius
+        assert!((area.map(25.0) - 5.0).abs() < 1e-12);
+        assert_eq!(area.map(100.0), 10.0);
+        assert_eq!(area.map(f64::NAN), 3.0);
+
+        let mut radius = RangeScale::radius().range(0.0, 10.0);
+        radius.train(&[0.0, 100.0]);
+        assert_eq!(radius.map(25.0), 2.5);
+
+        // a single value sits in the middle of the range
+        let mut single = RangeScale::radius();
+        single.train(&[7.0]);
+        assert_eq!(single.map(7.0), 3.75);
+    }
+
+    #[test]
+    fn test_alpha_and_linewidth_scales_with_breaks() {
+        let mut alpha = RangeScale::alpha().limits(0.0, 10.0);
+        alpha.train(&[3.0]);
+        assert!((alpha.map(5.0) - 0.55).abs() < 1e-12);
+        assert_eq!(alpha.map(20.0), 1.0);
+
+        let mut linewidth = RangeScale::linewidth().range(1.0, 5.0);
+        linewidth.train(&[0.0, 4.0]);
+        let legend = linewidth.legend();
+        assert_eq!(legend.len(), 5);
+        assert_eq!(legend[1], (1.0, 2.0, "1".to_string()));
+    }
+}
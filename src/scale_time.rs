@@ -0,0 +1,513 @@
+// A position scale for dates and datetimes. The data layer hands temporal columns over
+// as seconds since the unix epoch (see data_temporal.rs), and the scale maps those like a
+// continuous scale, but puts its breaks on calendar boundaries (whole minutes, midnight,
+// Mondays, the first of a month, quarter or year), with the interval picked from the span
+// of the data, and labels them in a format that suits the interval.
+//
+// Breaks and labels are worked out in the scale's time zone, UTC unless set, so daily
+// breaks fall on local midnight on both sides of a daylight saving change. Local times
+// that don't exist (skipped by a clock change) get no break.
+
+use std::fmt::Write;
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use chrono_tz::Tz;
+
+use crate::scale_breaks::{MinorBreaks, nice_step};
+use crate::scales::{ContinuousScale, Expansion, OutOfBounds, Scale};
+
+// no more breaks than this, whatever interval was asked for
+const MAX_BREAKS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CalendarUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    // starting on Mondays
+    Week,
+    Month,
+    // starting in January, April, July and October
+    Quarter,
+    Year,
+}
+
+impl CalendarUnit {
+    // average length in seconds, for choosing an interval
+    fn seconds(&self) -> f64 {
+        match self {
+            CalendarUnit::Second => 1.0,
+            CalendarUnit::Minute => 60.0,
+            CalendarUnit::Hour => 3_600.0,
+            CalendarUnit::Day => 86_400.0,
+            CalendarUnit::Week => 604_800.0,
+            CalendarUnit::Month => 2_629_746.0,
+            CalendarUnit::Quarter => 7_889_238.0,
+            CalendarUnit::Year => 31_556_952.0,
+        }
+    }
+}
+
+// `count` calendar units, e.g. 15 minutes or 2 years
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeInterval {
+    pub count: u32,
+    pub unit: CalendarUnit,
+}
+
+// candidates for automatic breaks, shortest first; longer spans get a nice number of years
+const INTERVALS: [(u32, CalendarUnit); 22] = [
+    (1, CalendarUnit::Second),
+    (2, CalendarUnit::Second),
+    (5, CalendarUnit::Second),
+    (10, CalendarUnit::Second),
+    (15, CalendarUnit::Second),
+    (30, CalendarUnit::Second),
+    (1, CalendarUnit::Minute),
+    (2, CalendarUnit::Minute),
+    (5, CalendarUnit::Minute),
+    (10, CalendarUnit::Minute),
+    (15, CalendarUnit::Minute),
+    (30, CalendarUnit::Minute),
+    (1, CalendarUnit::Hour),
+    (3, CalendarUnit::Hour),
+    (6, CalendarUnit::Hour),
+    (12, CalendarUnit::Hour),
+    (1, CalendarUnit::Day),
+    (2, CalendarUnit::Day),
+    (1, CalendarUnit::Week),
+    (1, CalendarUnit::Month),
+    (1, CalendarUnit::Quarter),
+    (6, CalendarUnit::Month),
+];
+
+impl TimeInterval {
+    pub fn new(count: u32, unit: CalendarUnit) -> Self {
+        TimeInterval {
+            count: count.max(1),
+            unit,
+        }
+    }
+
+    // the shortest interval giving at most `n` intervals over `span` seconds
+    pub fn for_span(span: f64, n: usize) -> Self {
+        let n = n.max(1);
+        INTERVALS
+            .iter()
+            .map(|&(count, unit)| TimeInterval::new(count, unit))
+            .find(|interval| span / interval.seconds() <= n as f64)
+            .unwrap_or_else(|| {
+                let years = nice_step(span / CalendarUnit::Year.seconds(), n);
+                TimeInterval::new(years.round().max(1.0) as u32, CalendarUnit::Year)
+            })
+    }
+
+    fn seconds(&self) -> f64 {
+        self.count as f64 * self.unit.seconds()
+    }
+
+    // the latest boundary of this interval at or before `local`
+    pub fn floor(&self, local: NaiveDateTime) -> NaiveDateTime {
+        let count = self.count;
+        let date = local.date();
+        let (hour, minute, second) = (local.hour(), local.minute(), local.second());
+        let at_midnight = |date: NaiveDate| date.and_hms_opt(0, 0, 0).unwrap_or(local);
+        let first_of_month = |month0: u32| {
+            NaiveDate::from_ymd_opt(date.year(), month0 + 1, 1).map_or(local, at_midnight)
+        };
+        let time = |hour, minute, second| date.and_hms_opt(hour, minute, second).unwrap_or(local);
+
+        match self.unit {
+            CalendarUnit::Second => time(hour, minute, second - second % count),
+            CalendarUnit::Minute => time(hour, minute - minute % count, 0),
+            CalendarUnit::Hour => time(hour - hour % count, 0, 0),
+            // whole multiples of the interval since 1970-01-01
+            CalendarUnit::Day => {
+                let days = (date - NaiveDate::default()).num_days();
+                at_midnight(date - Duration::days(days.rem_euclid(count as i64)))
+            }
+            CalendarUnit::Week => {
+                let monday = date.weekday().num_days_from_monday();
+                at_midnight(date - Duration::days(monday as i64))
+            }
+            CalendarUnit::Month => first_of_month(date.month0() - date.month0() % count),
+            CalendarUnit::Quarter => first_of_month(date.month0() - date.month0() % (3 * count)),
+            CalendarUnit::Year => {
+                let year = date.year() - date.year().rem_euclid(count as i32);
+                NaiveDate::from_ymd_opt(year, 1, 1).map_or(local, at_midnight)
+            }
+        }
+    }
+
+    // the boundary one interval after `local`, None past the end of the calendar
+    pub fn next(&self, local: NaiveDateTime) -> Option<NaiveDateTime> {
+        let count = self.count as i64;
+        match self.unit {
+            CalendarUnit::Second => local.checked_add_signed(Duration::seconds(count)),
+            CalendarUnit::Minute => local.checked_add_signed(Duration::minutes(count)),
+            CalendarUnit::Hour => local.checked_add_signed(Duration::hours(count)),
+            CalendarUnit::Day => local.checked_add_signed(Duration::days(count)),
+            CalendarUnit::Week => local.checked_add_signed(Duration::weeks(count)),
+            CalendarUnit::Month => local.checked_add_months(Months::new(self.count)),
+            CalendarUnit::Quarter => local.checked_add_months(Months::new(3 * self.count)),
+            CalendarUnit::Year => local.checked_add_months(Months::new(12 * self.count)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimeBreaks {
+    // calendar breaks at an interval giving at most this many intervals
+    Auto(usize),
+    Every(TimeInterval),
+    // in seconds since the epoch
+    Manual(Vec<f64>),
+    None,
+}
+
+impl Default for TimeBreaks {
+    fn default() -> Self {
+        TimeBreaks::Auto(5)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum TimeLabels {
+    // a format suiting the interval between breaks, e.g. "%H:%M" or "%b %Y"
+    #[default]
+    Auto,
+    // a strftime format, e.g. "%d/%m/%Y"
+    Format(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeScale {
+    // the domain, limits, expansion and mapping, in seconds since the epoch
+    continuous: ContinuousScale,
+    time_zone: Tz,
+    breaks: TimeBreaks,
+    minor_breaks: MinorBreaks,
+    labels: TimeLabels,
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        TimeScale::new()
+    }
+}
+
+impl TimeScale {
+    pub fn new() -> Self {
+        TimeScale {
+            continuous: ContinuousScale::new(),
+            time_zone: Tz::UTC,
+            breaks: TimeBreaks::default(),
+            minor_breaks: MinorBreaks::default(),
+            labels: TimeLabels::default(),
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.continuous = self.continuous.name(name);
+        self
+    }
+
+    // fixed domain in seconds since the epoch, see `epoch_seconds`
+    pub fn limits(mut self, min: f64, max: f64) -> Self {
+        self.continuous = self.continuous.limits(min, max);
+        self
+    }
+
+    pub fn expand(mut self, expand: Expansion) -> Self {
+        self.continuous = self.continuous.expand(expand);
+        self
+    }
+
+    pub fn oob(mut self, oob: OutOfBounds) -> Self {
+        self.continuous = self.continuous.oob(oob);
+        self
+    }
+
+    pub fn range(mut self, start: f64, end: f64) -> Self {
+        self.continuous = self.continuous.range(start, end);
+        self
+    }
+
+    // where breaks are placed and labels read, e.g. chrono_tz::Europe::London
+    pub fn time_zone(mut self, time_zone: Tz) -> Self {
+        self.time_zone = time_zone;
+        self
+    }
+
+    pub fn breaks(mut self, breaks: TimeBreaks) -> Self {
+        self.breaks = breaks;
+        self
+    }
+
+    pub fn minor_breaks(mut self, minor_breaks: MinorBreaks) -> Self {
+        self.minor_breaks = minor_breaks;
+        self
+    }
+
+    pub fn labels(mut self, labels: TimeLabels) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    pub fn get_time_zone(&self) -> Tz {
+        self.time_zone
+    }
+
+    // the interval between automatic breaks, or the one asked for
+    pub fn interval(&self) -> TimeInterval {
+        let (min, max) = self.break_limits();
+        match &self.breaks {
+            TimeBreaks::Every(interval) => *interval,
+            TimeBreaks::Auto(n) => TimeInterval::for_span(max - min, *n),
+            _ => TimeInterval::for_span(max - min, 5),
+        }
+    }
+
+    fn break_limits(&self) -> (f64, f64) {
+        self.continuous
+            .get_limits()
+            .unwrap_or_else(|| self.dimension())
+    }
+
+    fn zoned(&self, seconds: f64) -> Option<DateTime<Tz>> {
+        if !seconds.is_finite() {
+            return None;
+        }
+        let utc = DateTime::from_timestamp(seconds.floor() as i64, 0)?;
+        Some(utc.with_timezone(&self.time_zone))
+    }
+
+    fn calendar_breaks(&self, (min, max): (f64, f64), interval: TimeInterval) -> Vec<f64> {
+        let (Some(start), Some(end)) = (self.zoned(min), self.zoned(max)) else {
+            return Vec::new();
+        };
+        let end = end.naive_local();
+        let mut breaks = Vec::new();
+        let mut current = Some(interval.floor(start.naive_local()));
+        while let Some(local) = current.filter(|local| *local <= end) {
+            // skipped local times have no break, repeated ones the first
+            if let Some(time) = self.time_zone.from_local_datetime(&local).earliest() {
+                breaks.push(epoch_seconds(&time));
+            }
+            if breaks.len() >= MAX_BREAKS {
+                break;
+            }
+            current = interval.next(local);
+        }
+        breaks
+    }
+}
+
+impl Scale for TimeScale {
+    fn train(&mut self, values: &[f64]) {
+        self.continuous.train(values);
+    }
+
+    fn get_limits(&self) -> Option<(f64, f64)> {
+        self.continuous.get_limits()
+    }
+
+    fn dimension(&self) -> (f64, f64) {
+        self.continuous.dimension()
+    }
+
+    fn map(&self, value: f64) -> f64 {
+        self.continuous.map(value)
+    }
+
+    fn inverse(&self, value: f64) -> f64 {
+        self.continuous.inverse(value)
+    }
+
+    fn get_breaks(&self) -> Vec<f64> {
+        let limits = self.break_limits();
+        let breaks = match &self.breaks {
+            TimeBreaks::Auto(_) | TimeBreaks::Every(_) => {
+                self.calendar_breaks(limits, self.interval())
+            }
+            TimeBreaks::Manual(breaks) => breaks.clone(),
+            TimeBreaks::None => Vec::new(),
+        };
+        let (min, max) = limits;
+        breaks
+            .into_iter()
+            .filter(|&value| value >= min && value <= max)
+            .collect()
+    }
+
+    fn get_minor_breaks(&self) -> Vec<f64> {
+        self.minor_breaks
+            .compute(&self.get_breaks(), self.dimension())
+    }
+
+    fn get_labels(&self) -> Vec<String> {
+        let times: Vec<Option<DateTime<Tz>>> = self
+            .get_breaks()
+            .into_iter()
+            .map(|seconds| self.zoned(seconds))
+            .collect();
+        let format = match &self.labels {
+            TimeLabels::Format(format) => format.as_str(),
+            TimeLabels::Auto => match auto_format(self.interval().unit, &times) {
+                Some(format) => format,
+                None => return times.iter().map(quarter).collect(),
+            },
+        };
+        times.iter().map(|time| format_time(time, format)).collect()
+    }
+
+    fn get_name(&self) -> Option<&str> {
+        self.continuous.get_name()
+    }
+
+    fn box_clone(&self) -> Box<dyn Scale> {
+        Box::new(TimeScale {
+            continuous: self.continuous.untrained(),
+            ..self.clone()
+        })
+    }
+}
+
+// seconds since the unix epoch, the unit time scales work in
+pub fn epoch_seconds<T: TimeZone>(time: &DateTime<T>) -> f64 {
+    time.timestamp() as f64 + time.timestamp_subsec_nanos() as f64 * 1e-9
+}
+
+// the strftime format for breaks this far apart; dates are left out when every break is
+// on the same day, years when they're all in the same year. None for quarters
+fn auto_format(unit: CalendarUnit, times: &[Option<DateTime<Tz>>]) -> Option<&'static str> {
+    let present: Vec<&DateTime<Tz>> = times.iter().flatten().collect();
+    let same = |key: fn(&DateTime<Tz>) -> (i32, u32)| {
+        present.windows(2).all(|pair| key(pair[0]) == key(pair[1]))
+    };
+    let same_day = same(|time| (time.year(), time.ordinal()));
+    let same_year = same(|time| (time.year(), 0));
+
+    Some(match unit {
+        CalendarUnit::Second if same_day => "%H:%M:%S",
+        CalendarUnit::Second => "%b %d %H:%M:%S",
+        CalendarUnit::Minute | CalendarUnit::Hour if same_day => "%H:%M",
+        CalendarUnit::Minute | CalendarUnit::Hour => "%b %d %H:%M",
+        CalendarUnit::Day | CalendarUnit::Week if same_year => "%b %d",
+        CalendarUnit::Day | CalendarUnit::Week => "%Y-%m-%d",
+        CalendarUnit::Month => "%b %Y",
+        CalendarUnit::Quarter => return None,
+        CalendarUnit::Year => "%Y",
+    })
+}
+
+// e.g. "2024 Q3"
+fn quarter(time: &Option<DateTime<Tz>>) -> String {
+    match time {
+        Some(time) => format!("{} Q{}", time.year(), time.month0() / 3 + 1),
+        None => "NA".to_string(),
+    }
+}
+
+fn format_time(time: &Option<DateTime<Tz>>, format: &str) -> String {
+    let Some(time) = time else {
+        return "NA".to_string();
+    };
+    // an invalid format fails to write rather than panicking
+    let mut text = String::new();
+    match write!(text, "{}", time.format(format)) {
+        Ok(()) => text,
+        Err(_) => "NA".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seconds(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> f64 {
+        let time = Tz::UTC
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap();
+        epoch_seconds(&time)
+    }
+
+    fn trained(values: &[f64]) -> TimeScale {
+        let mut scale = TimeScale::new().expand(Expansion::none());
+        scale.train(values);
+        scale
+    }
+
+    #[test]
+    fn test_intervals_follow_the_span() {
+        let unit = |span: f64| TimeInterval::for_span(span, 5);
+        assert_eq!(unit(40.0), TimeInterval::new(10, CalendarUnit::Second));
+        assert_eq!(unit(3_600.0), TimeInterval::new(15, CalendarUnit::Minute));
+        assert_eq!(unit(86_400.0), TimeInterval::new(6, CalendarUnit::Hour));
+        assert_eq!(
+            unit(30.0 * 86_400.0),
+            TimeInterval::new(1, CalendarUnit::Week)
+        );
+        assert_eq!(
+            unit(365.0 * 86_400.0),
+            TimeInterval::new(1, CalendarUnit::Quarter)
+        );
+        assert_eq!(
+            unit(40.0 * 365.0 * 86_400.0),
+            TimeInterval::new(10, CalendarUnit::Year)
+        );
+    }
+
+    #[test]
+    fn test_breaks_fall_on_calendar_boundaries() {
+        let hours = trained(&[seconds(2024, 3, 5, 9, 20), seconds(2024, 3, 5, 10, 10)]);
+        assert_eq!(
+            hours.get_labels(),
+            vec!["09:20", "09:30", "09:40", "09:50", "10:00", "10:10"]
+        );
+
+        // months have different lengths but breaks stay on the first
+        let months = trained(&[seconds(2023, 11, 20, 0, 0), seconds(2024, 3, 10, 0, 0)]);
+        assert_eq!(
+            months.get_breaks(),
+            vec![
+                seconds(2023, 12, 1, 0, 0),
+                seconds(2024, 1, 1, 0, 0),
+                seconds(2024, 2, 1, 0, 0),
+                seconds(2024, 3, 1, 0, 0),
+            ]
+        );
+        assert_eq!(
+            months.get_labels(),
+            vec!["Dec 2023", "Jan 2024", "Feb 2024", "Mar 2024"]
+        );
+
+        let year = trained(&[seconds(2023, 2, 1, 0, 0), seconds(2024, 1, 20, 0, 0)]);
+        assert_eq!(
+            year.get_labels(),
+            vec!["2023 Q2", "2023 Q3", "2023 Q4", "2024 Q1"]
+        );
+
+        let weeks = trained(&[seconds(2024, 1, 1, 0, 0), seconds(2024, 1, 29, 0, 0)])
+            .breaks(TimeBreaks::Every(TimeInterval::new(2, CalendarUnit::Week)))
+            .labels(TimeLabels::Format("%d/%m".to_string()));
+        assert_eq!(weeks.get_labels(), vec!["01/01", "15/01", "29/01"]);
+    }
+
+    #[test]
+    fn test_breaks_and_labels_use_the_time_zone() {
+        // a day across the end of daylight saving time in London
+        let scale = TimeScale::new()
+            .time_zone(chrono_tz::Europe::London)
+            .expand(Expansion::none())
+            .breaks(TimeBreaks::Every(TimeInterval::new(1, CalendarUnit::Day)))
+            .limits(seconds(2024, 10, 26, 0, 0), seconds(2024, 10, 28, 0, 0));
+        // local midnight is 23:00 UTC in summer time and 00:00 UTC after it
+        assert_eq!(
+            scale.get_breaks(),
+            vec![seconds(2024, 10, 26, 23, 0), seconds(2024, 10, 28, 0, 0)]
+        );
+        assert_eq!(scale.get_labels(), vec!["Oct 27", "Oct 28"]);
+    }
+}
@@ -0,0 +1,369 @@
+// Transformations applied by a continuous scale before it maps values, so that data
+// spanning orders of magnitude can be shown on a log axis and so on. Scales keep their
+// domain in transformed units but take and report values (limits, breaks) in data units.
+//
+// Values outside a transformation's domain, such as zero or negatives on a log scale,
+// become NaN and are treated as missing.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::scale_breaks::extended_breaks;
+
+type TransformFn = Arc<dyn Fn(f64) -> f64 + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub enum ScaleTransform {
+    #[default]
+    Identity,
+    Log10,
+    Log2,
+    Ln,
+    Sqrt,
+    Reverse,
+    // sign(x) * log10(1 + |x| / threshold): linear around zero, logarithmic beyond
+    // the threshold, and defined for negative values
+    Symlog {
+        threshold: f64,
+    },
+    // asinh(x / (2 sigma)) / ln(base), a smooth log-like transformation through zero
+    PseudoLog {
+        sigma: f64,
+        base: f64,
+    },
+    // ln(p / (1 - p)) for proportions strictly between 0 and 1
+    Logit,
+    Custom {
+        name: String,
+        forward: TransformFn,
+        inverse: TransformFn,
+    },
+}
+
+impl fmt::Debug for ScaleTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScaleTransform::Symlog { threshold } => {
+                write!(f, "Symlog {{ threshold: {} }}", threshold)
+            }
+            ScaleTransform::PseudoLog { sigma, base } => {
+                write!(f, "PseudoLog {{ sigma: {}, base: {} }}", sigma, base)
+            }
+            ScaleTransform::Custom { name, .. } => write!(f, "Custom({:?})", name),
+            other => write!(f, "{}", other.name()),
+        }
+    }
+}
+
+impl PartialEq for ScaleTransform {
+    fn eq(&self, other: &Self) -> bool {
+        use ScaleTransform::*;
+        match (self, other) {
+            (Symlog { threshold: a }, Symlog { threshold: b }) => a == b,
+            (
+                PseudoLog { sigma, base },
+                PseudoLog {
+                    sigma: other_sigma,
+                    base: other_base,
+                },
+            ) => sigma == other_sigma && base == other_base,
+            // closures can't be compared, only recognised as the same ones
+            (
+                Custom {
+                    forward, inverse, ..
+                },
+                Custom {
+                    forward: other_forward,
+                    inverse: other_inverse,
+                    ..
+                },
+            ) => Arc::ptr_eq(forward, other_forward) && Arc::ptr_eq(inverse, other_inverse),
+            (a, b) => std::mem::discriminant(a) == std::mem::discriminant(b),
+        }
+    }
+}
+
+impl ScaleTransform {
+    pub fn symlog() -> Self {
+        ScaleTransform::Symlog { threshold: 1.0 }
+    }
+
+    pub fn pseudo_log() -> Self {
+        ScaleTransform::PseudoLog {
+            sigma: 1.0,
+            base: std::f64::consts::E,
+        }
+    }
+
+    // `inverse` must undo `forward` over the values being plotted
+    pub fn custom(
+        name: impl Into<String>,
+        forward: impl Fn(f64) -> f64 + Send + Sync + 'static,
+        inverse: impl Fn(f64) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        ScaleTransform::Custom {
+            name: name.into(),
+            forward: Arc::new(forward),
+            inverse: Arc::new(inverse),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            ScaleTransform::Identity => "identity",
+            ScaleTransform::Log10 => "log-10",
+            ScaleTransform::Log2 => "log-2",
+            ScaleTransform::Ln => "log-e",
+            ScaleTransform::Sqrt => "sqrt",
+            ScaleTransform::Reverse => "reverse",
+            ScaleTransform::Symlog { .. } => "symlog",
+            ScaleTransform::PseudoLog { .. } => "pseudo-log",
+            ScaleTransform::Logit => "logit",
+            ScaleTransform::Custom { name, .. } => name,
+        }
+    }
+
+    // the base of a logarithmic transformation
+    fn log_base(&self) -> Option<f64> {
+        match self {
+            ScaleTransform::Log10 => Some(10.0),
+            ScaleTransform::Log2 => Some(2.0),
+            ScaleTransform::Ln => Some(std::f64::consts::E),
+            _ => None,
+        }
+    }
+
+    // NaN outside the transformation's domain
+    pub fn transform(&self, value: f64) -> f64 {
+        match self {
+            ScaleTransform::Identity => value,
+            ScaleTransform::Log10 | ScaleTransform::Log2 | ScaleTransform::Ln if value <= 0.0 => {
+                f64::NAN
+            }
+            ScaleTransform::Log10 => value.log10(),
+            ScaleTransform::Log2 => value.log2(),
+            ScaleTransform::Ln => value.ln(),
+            ScaleTransform::Sqrt => match value >= 0.0 {
+                true => value.sqrt(),
+                false => f64::NAN,
+            },
+            ScaleTransform::Reverse => -value,
+            ScaleTransform::Symlog { threshold } => {
+                value.signum() * (1.0 + value.abs() / threshold).log10()
+            }
+            ScaleTransform::PseudoLog { sigma, base } => {
+                (value / (2.0 * sigma)).asinh() / base.ln()
+            }
+            ScaleTransform::Logit => match value > 0.0 && value < 1.0 {
+                true => (value / (1.0 - value)).ln(),
+                false => f64::NAN,
+            },
+            ScaleTransform::Custom { forward, .. } => forward(value),
+        }
+    }
+
+    pub fn inverse(&self, value: f64) -> f64 {
+        match self {
+            ScaleTransform::Identity => value,
+            ScaleTransform::Log10 | ScaleTransform::Log2 | ScaleTransform::Ln => {
+                self.log_base().unwrap_or(10.0).powf(value)
+            }
+            ScaleTransform::Sqrt => value * value,
+            ScaleTransform::Reverse => -value,
+            ScaleTransform::Symlog { threshold } => {
+                value.signum() * threshold * (10f64.powf(value.abs()) - 1.0)
+            }
+            ScaleTransform::PseudoLog { sigma, base } => 2.0 * sigma * (value * base.ln()).sinh(),
+            ScaleTransform::Logit => 1.0 / (1.0 + (-value).exp()),
+            ScaleTransform::Custom { inverse, .. } => inverse(value),
+        }
+    }
+
+    // the transformed range as (low, high), whichever way the transformation runs
+    pub fn transform_range(&self, (min, max): (f64, f64)) -> (f64, f64) {
+        let (a, b) = (self.transform(min), self.transform(max));
+        (a.min(b), a.max(b))
+    }
+
+    pub fn inverse_range(&self, (min, max): (f64, f64)) -> (f64, f64) {
+        let (a, b) = (self.inverse(min), self.inverse(max));
+        (a.min(b), a.max(b))
+    }
+
+    // about `n` breaks for a domain in data units: powers of the base for logarithms,
+    // powers either side of zero for symlog and pseudo-log, Extended breaks otherwise
+    pub fn default_breaks(&self, (min, max): (f64, f64), n: usize) -> Vec<f64> {
+        match self {
+            ScaleTransform::Symlog { .. } => symmetric_log_breaks(min, max, 10.0, n),
+            // powers of e or other fractional bases make poor labels
+            ScaleTransform::PseudoLog { base, .. } if base.fract() == 0.0 && *base >= 2.0 => {
+                symmetric_log_breaks(min, max, *base, n)
+            }
+            ScaleTransform::PseudoLog { .. } => symmetric_log_breaks(min, max, 10.0, n),
+            _ => match self.log_base() {
+                Some(base) => log_breaks(min, max, base, n),
+                None => extended_breaks(min, max, n),
+            },
+        }
+    }
+}
+
+// integer powers of `base`, filled in with 1-2-5 style multiples when the domain
+// covers too few of them to give about `n` breaks
+pub fn log_breaks(min: f64, max: f64, base: f64, n: usize) -> Vec<f64> {
+    if !(min > 0.0 && max > 0.0 && min.is_finite() && max.is_finite()) {
+        return Vec::new();
+    }
+    let log = |value: f64| value.ln() / base.ln();
+    let low = log(min).floor() as i32;
+    let high = log(max).ceil() as i32;
+    let wanted = n.saturating_sub(2).max(1);
+    let inside = |breaks: &[f64]| {
+        breaks
+            .iter()
+            .filter(|&&value| value >= min * (1.0 - 1e-10) && value <= max * (1.0 + 1e-10))
+            .count()
+    };
+
+    // every `by`-th power when there are many of them
+    let by = ((high - low) as usize / n.max(1)) + 1;
+    let powers: Vec<f64> = (low..=high)
+        .step_by(by)
+        .map(|power| base.powi(power))
+        .collect();
+    if inside(&powers) >= wanted {
+        return powers;
+    }
+
+    if base == 10.0 {
+        let multiples: [&[f64]; 3] = [&[1.0, 3.0], &[1.0, 2.0, 5.0], &[1.0, 2.0, 3.0, 5.0, 7.0]];
+        for multiples in multiples {
+            let breaks: Vec<f64> = (low..=high)
+                .flat_map(|power| multiples.iter().map(move |m| m * base.powi(power)))
+                .collect();
+            if inside(&breaks) >= wanted {
+                return breaks;
+            }
+        }
+    }
+    extended_breaks(min, max, n)
+}
+
+// zero and integer powers of `base` on either side of it (..., -10, -1, 0, 1, 10, ...),
+// every few powers counting down from the largest when there are many of them
+pub fn symmetric_log_breaks(min: f64, max: f64, base: f64, n: usize) -> Vec<f64> {
+    if !(min.is_finite() && max.is_finite()) {
+        return Vec::new();
+    }
+    let largest = min.abs().max(max.abs());
+    let high = (largest.ln() / base.ln()).ceil().max(0.0) as i32;
+    let sides = match min < 0.0 && max > 0.0 {
+        true => 2,
+        false => 1,
+    };
+    let by = ((high as usize + 1) * sides / n.max(1)) + 1;
+    let powers: Vec<f64> = (0..=high)
+        .rev()
+        .step_by(by)
+        .map(|power| base.powi(power))
+        .collect();
+
+    let mut breaks: Vec<f64> = powers.iter().map(|&power| -power).collect();
+    breaks.push(0.0);
+    breaks.extend(powers.iter().rev());
+    // the powers beyond the far side of zero are left out; the scale drops the rest
+    let breaks: Vec<f64> = breaks
+        .into_iter()
+        .filter(|&value| (value >= 0.0 || min < 0.0) && (value <= 0.0 || max > 0.0))
+        .collect();
+    let inside = breaks
+        .iter()
+        .filter(|&&value| value >= min && value <= max)
+        .count();
+    match inside >= 3 {
+        true => breaks,
+        false => extended_breaks(min, max, n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transforms_round_trip() {
+        let transforms = [
+            ScaleTransform::Log10,
+            ScaleTransform::Log2,
+            ScaleTransform::Ln,
+            ScaleTransform::Sqrt,
+            ScaleTransform::Reverse,
+            ScaleTransform::symlog(),
+            ScaleTransform::pseudo_log(),
+            ScaleTransform::Logit,
+            ScaleTransform::custom("cube", |x| x.powi(3), f64::cbrt),
+        ];
+        for transform in transforms {
+            for value in [0.25, 0.5, 0.75] {
+                let round_trip = transform.inverse(transform.transform(value));
+                assert!((round_trip - value).abs() < 1e-12, "{:?}", transform);
+            }
+        }
+
+        assert_eq!(ScaleTransform::Log10.transform(1000.0), 3.0);
+        assert_eq!(ScaleTransform::Log2.transform(8.0), 3.0);
+        assert_eq!(
+            ScaleTransform::Reverse.transform_range((1.0, 5.0)),
+            (-5.0, -1.0)
+        );
+        assert_eq!(ScaleTransform::symlog().transform(-9.0), -1.0);
+    }
+
+    #[test]
+    fn test_values_outside_the_domain_are_missing() {
+        assert!(ScaleTransform::Log10.transform(0.0).is_nan());
+        assert!(ScaleTransform::Ln.transform(-1.0).is_nan());
+        assert!(ScaleTransform::Sqrt.transform(-4.0).is_nan());
+        assert!(ScaleTransform::Logit.transform(1.0).is_nan());
+        // symlog and pseudo-log are defined everywhere
+        assert!(ScaleTransform::pseudo_log().transform(-100.0).is_finite());
+    }
+
+    #[test]
+    fn test_log_breaks() {
+        assert_eq!(
+            log_breaks(1.0, 1e4, 10.0, 5),
+            vec![1.0, 10.0, 100.0, 1e3, 1e4]
+        );
+        // many decades: every third power
+        assert_eq!(log_breaks(1.0, 1e10, 10.0, 5), vec![1.0, 1e3, 1e6, 1e9]);
+        // a single decade is filled in
+        assert_eq!(
+            log_breaks(2.0, 40.0, 10.0, 5),
+            vec![1.0, 3.0, 10.0, 30.0, 100.0, 300.0]
+        );
+        assert_eq!(log_breaks(1.0, 64.0, 2.0, 5), vec![1.0, 4.0, 16.0, 64.0]);
+    }
+
+    #[test]
+    fn test_symmetric_log_breaks() {
+        let symlog = ScaleTransform::symlog();
+        assert_eq!(
+            symlog.default_breaks((0.0, 1000.0), 5),
+            vec![0.0, 1.0, 10.0, 100.0, 1000.0]
+        );
+        assert_eq!(
+            symlog.default_breaks((-1000.0, 1000.0), 5),
+            vec![-1000.0, -10.0, 0.0, 10.0, 1000.0]
+        );
+        assert_eq!(
+            ScaleTransform::pseudo_log().default_breaks((-50.0, 0.0), 5),
+            vec![-100.0, -10.0, -1.0, 0.0]
+        );
+        // too close to zero for powers to help
+        assert_eq!(
+            symlog.default_breaks((0.0, 0.5), 5),
+            extended_breaks(0.0, 0.5, 5)
+        );
+    }
+}
@@ -0,0 +1,463 @@
+// Scales turn data values into visual values. Position scales map into the 0..1 space
+// of the panel (see coord.rs), but any output range works, e.g. pixels.
+//
+// A continuous scale is trained on every layer's values, widened by its expansion and
+// then mapped linearly. Explicit limits replace the trained domain; values outside them
+// are handled by the out-of-bounds policy. Breaks and their labels are configured with
+// the types in scale_breaks.rs and scale_labels.rs. With a transformation (see
+// scale_transform.rs) the domain is kept in transformed units, but values, limits and
+// breaks going in and out of the scale stay in data units.
+//
+// Categorical data and dates have scales of their own, in scale_discrete.rs and
+// scale_time.rs.
+
+use crate::scale_breaks::{Breaks, MinorBreaks, nice_domain};
+use crate::scale_discrete::DiscreteScale;
+use crate::scale_labels::LabelFormat;
+use crate::scale_transform::ScaleTransform;
+
+// data values → visual values for one aesthetic
+pub trait Scale {
+    // widen the domain to include these values; non-finite values are ignored
+    fn train(&mut self, values: &[f64]);
+
+    // the domain: explicit limits, else the trained range, else None
+    fn get_limits(&self) -> Option<(f64, f64)>;
+
+    // the domain after expansion, i.e. what maps onto the ends of the output range;
+    // in transformed units for transformed scales
+    fn dimension(&self) -> (f64, f64);
+
+    // data value → visual value; NaN for missing or censored values
+    fn map(&self, value: f64) -> f64;
+
+    // visual value → data value, e.g. for placing axes
+    fn inverse(&self, value: f64) -> f64;
+
+    // tick positions in data units, all within the limits
+    fn get_breaks(&self) -> Vec<f64>;
+
+    // positions of the lighter grid lines between the breaks
+    fn get_minor_breaks(&self) -> Vec<f64> {
+        Vec::new()
+    }
+
+    // one label per break
+    fn get_labels(&self) -> Vec<String>;
+
+    // axis or legend title, overriding the plot's labels
+    fn get_name(&self) -> Option<&str> {
+        None
+    }
+
+    // an untrained copy, so a plot can train its scales afresh on every build
+    fn box_clone(&self) -> Box<dyn Scale>;
+
+    // the value the scale works with internally, NaN if it can't be transformed
+    fn transform(&self, value: f64) -> f64 {
+        value
+    }
+
+    // the scale as a discrete one, so categorical values can be mapped to positions
+    fn as_discrete(&self) -> Option<&DiscreteScale> {
+        None
+    }
+
+    fn as_discrete_mut(&mut self) -> Option<&mut DiscreteScale> {
+        None
+    }
+
+    fn map_all(&self, values: &[f64]) -> Vec<f64> {
+        values.iter().map(|&value| self.map(value)).collect()
+    }
+}
+
+impl Clone for Box<dyn Scale> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+// what happens to values outside explicit limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutOfBounds {
+    // treated as missing (not drawn)
+    #[default]
+    Censor,
+    // moved onto the nearest limit
+    Squish,
+    // mapped as they are, ending up outside the output range
+    Keep,
+}
+
+// padding added to each side of the domain: `mult` times its width plus `add`.
+// it's applied after the transform, so `add` is in transformed units (under log10,
+// an `add` of 1 pads by a factor of ten)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Expansion {
+    pub mult: f64,
+    pub add: f64,
+}
+
+impl Expansion {
+    pub fn new(mult: f64, add: f64) -> Self {
+        Expansion { mult, add }
+    }
+
+    pub fn none() -> Self {
+        Expansion::new(0.0, 0.0)
+    }
+
+    pub fn apply(&self, (min, max): (f64, f64)) -> (f64, f64) {
+        let padding = (max - min) * self.mult + self.add;
+        (min - padding, max + padding)
+    }
+}
+
+impl Default for Expansion {
+    fn default() -> Self {
+        Expansion::new(0.05, 0.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContinuousScale {
+    name: Option<String>,
+    // in transformed units
+    trained: Option<(f64, f64)>,
+    // in data units
+    limits: Option<(f64, f64)>,
+    transform: ScaleTransform,
+    expand: Expansion,
+    oob: OutOfBounds,
+    // round the trained domain outwards to nice numbers
+    nice: bool,
+    range: (f64, f64),
+    breaks: Breaks,
+    minor_breaks: MinorBreaks,
+    labels: LabelFormat,
+}
+
+impl Default for ContinuousScale {
+    fn default() -> Self {
+        ContinuousScale::new()
+    }
+}
+
+impl ContinuousScale {
+    pub fn new() -> Self {
+        ContinuousScale {
+            name: None,
+            trained: None,
+            limits: None,
+            transform: ScaleTransform::default(),
+            expand: Expansion::default(),
+            oob: OutOfBounds::default(),
+            nice: false,
+            range: (0.0, 1.0),
+            breaks: Breaks::default(),
+            minor_breaks: MinorBreaks::default(),
+            labels: LabelFormat::default(),
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    // fixed domain instead of the trained one
+    pub fn limits(mut self, min: f64, max: f64) -> Self {
+        self.limits = Some((min, max));
+        self
+    }
+
+    pub fn transform(mut self, transform: ScaleTransform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn log10(self) -> Self {
+        self.transform(ScaleTransform::Log10)
+    }
+
+    pub fn sqrt(self) -> Self {
+        self.transform(ScaleTransform::Sqrt)
+    }
+
+    pub fn reverse(self) -> Self {
+        self.transform(ScaleTransform::Reverse)
+    }
+
+    pub fn expand(mut self, expand: Expansion) -> Self {
+        self.expand = expand;
+        self
+    }
+
+    pub fn oob(mut self, oob: OutOfBounds) -> Self {
+        self.oob = oob;
+        self
+    }
+
+    pub fn nice(mut self) -> Self {
+        self.nice = true;
+        self
+    }
+
+    // output range, (0, 1) by default
+    pub fn range(mut self, start: f64, end: f64) -> Self {
+        self.range = (start, end);
+        self
+    }
+
+    pub fn breaks(mut self, breaks: Breaks) -> Self {
+        self.breaks = breaks;
+        self
+    }
+
+    pub fn minor_breaks(mut self, minor_breaks: MinorBreaks) -> Self {
+        self.minor_breaks = minor_breaks;
+        self
+    }
+
+    pub fn labels(mut self, labels: LabelFormat) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    pub fn get_range(&self) -> (f64, f64) {
+        self.range
+    }
+
+    pub(crate) fn untrained(&self) -> Self {
+        ContinuousScale {
+            trained: None,
+            ..self.clone()
+        }
+    }
+
+    // explicit limits in transformed units, else the trained domain; an explicit limit
+    // that can't be transformed, such as zero on a log scale, is replaced by that end of
+    // the trained domain
+    fn transformed_limits(&self) -> Option<(f64, f64)> {
+        let trained = match (self.trained, self.nice) {
+            (Some(domain), true) => Some(nice_domain(domain)),
+            (domain, _) => domain,
+        };
+        let Some((min, max)) = self.limits else {
+            return trained;
+        };
+        let (min, max) = (self.transform.transform(min), self.transform.transform(max));
+        match (min.is_finite(), max.is_finite()) {
+            (true, true) => Some((min.min(max), min.max(max))),
+            (false, true) => trained.map(|(low, _)| (low.min(max), max)),
+            (true, false) => trained.map(|(_, high)| (min, high.max(min))),
+            (false, false) => trained,
+        }
+    }
+}
+
+impl Scale for ContinuousScale {
+    fn train(&mut self, values: &[f64]) {
+        let transformed = values.iter().map(|&value| self.transform.transform(value));
+        for value in transformed.filter(|value| value.is_finite()) {
+            self.trained = Some(match self.trained {
+                None => (value, value),
+                Some((min, max)) => (min.min(value), max.max(value)),
+            });
+        }
+    }
+
+    fn get_limits(&self) -> Option<(f64, f64)> {
+        self.transformed_limits()
+            .map(|limits| self.transform.inverse_range(limits))
+    }
+
+    fn dimension(&self) -> (f64, f64) {
+        match self.transformed_limits() {
+            None => (0.0, 1.0),
+            // a single value still needs some room around it
+            Some((min, max)) if min == max => (min - 0.5, max + 0.5),
+            Some(limits) => self.expand.apply(limits),
+        }
+    }
+
+    fn map(&self, value: f64) -> f64 {
+        let value = self.transform.transform(value);
+        let limits = self.limits.and(self.transformed_limits());
+        let value = match (limits, self.oob) {
+            (Some((min, max)), OutOfBounds::Censor) if value < min || value > max => f64::NAN,
+            (Some((min, max)), OutOfBounds::Squish) => value.clamp(min, max),
+            _ => value,
+        };
+        let (min, max) = self.dimension();
+        let (start, end) = self.range;
+        start + (value - min) / (max - min) * (end - start)
+    }
+
+    fn inverse(&self, value: f64) -> f64 {
+        let (min, max) = self.dimension();
+        let (start, end) = self.range;
+        self.transform
+            .inverse(min + (value - start) / (end - start) * (max - min))
+    }
+
+    fn get_breaks(&self) -> Vec<f64> {
+        let transformed = self
+            .transformed_limits()
+            .unwrap_or_else(|| self.dimension());
+        let limits = self.transform.inverse_range(transformed);
+        let breaks = match self.breaks {
+            Breaks::Extended(n) => self.transform.default_breaks(limits, n),
+            _ => self.breaks.compute(limits),
+        };
+        // kept if inside the limits, tolerating rounding error in breaks right on one
+        let (min, max) = transformed;
+        let tolerance = (max - min).abs() * 1e-10;
+        breaks
+            .into_iter()
+            .filter(|&value| {
+                let value = self.transform.transform(value);
+                value >= min - tolerance && value <= max + tolerance
+            })
+            .collect()
+    }
+
+    // evenly spaced in transformed units, e.g. halfway between decades on a log scale
+    fn get_minor_breaks(&self) -> Vec<f64> {
+        let transform = |values: Vec<f64>| -> Vec<f64> {
+            values
+                .into_iter()
+                .map(|value| self.transform.transform(value))
+                .collect()
+        };
+        let minor_breaks = match &self.minor_breaks {
+            MinorBreaks::Manual(breaks) => MinorBreaks::Manual(transform(breaks.clone())),
+            other => other.clone(),
+        };
+        minor_breaks
+            .compute(&transform(self.get_breaks()), self.dimension())
+            .into_iter()
+            .map(|value| self.transform.inverse(value))
+            .collect()
+    }
+
+    fn get_labels(&self) -> Vec<String> {
+        self.labels.format_all(&self.get_breaks())
+    }
+
+    fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn transform(&self, value: f64) -> f64 {
+        self.transform.transform(value)
+    }
+
+    fn box_clone(&self) -> Box<dyn Scale> {
+        Box::new(self.untrained())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_training_expansion_and_mapping() {
+        let mut scale = ContinuousScale::new().range(100.0, 300.0);
+        scale.train(&[2.0, 4.0]);
+        scale.train(&[12.0, f64::NAN]);
+
+        assert_eq!(scale.get_limits(), Some((2.0, 12.0)));
+        assert_eq!(scale.dimension(), (1.5, 12.5));
+        assert_eq!(scale.map(1.5), 100.0);
+        assert_eq!(scale.map(7.0), 200.0);
+        assert_eq!(scale.inverse(300.0), 12.5);
+        assert!(scale.map(f64::NAN).is_nan());
+
+        // clones used by plots start untrained
+        assert_eq!(scale.box_clone().get_limits(), None);
+    }
+
+    #[test]
+    fn test_limits_and_out_of_bounds_policies() {
+        let scale = ContinuousScale::new()
+            .limits(0.0, 10.0)
+            .expand(Expansion::none());
+        assert_eq!(scale.map(5.0), 0.5);
+        assert!(scale.map(11.0).is_nan());
+
+        let squished = scale.clone().oob(OutOfBounds::Squish);
+        assert_eq!(squished.map_all(&[-5.0, 15.0]), vec![0.0, 1.0]);
+
+        let kept = scale.oob(OutOfBounds::Keep);
+        assert_eq!(kept.map(15.0), 1.5);
+    }
+
+    #[test]
+    fn test_log_scale_works_in_data_units() {
+        let mut scale = ContinuousScale::new().log10().expand(Expansion::none());
+        scale.train(&[1.0, 10.0, 1000.0, 0.0, -5.0]);
+
+        assert_eq!(scale.get_limits(), Some((1.0, 1000.0)));
+        assert_eq!(scale.dimension(), (0.0, 3.0));
+        assert!((scale.map(100.0) - 2.0 / 3.0).abs() < 1e-12);
+        assert!(scale.map(0.0).is_nan());
+        assert!((scale.inverse(1.0) - 1000.0).abs() < 1e-9);
+
+        assert_eq!(scale.get_labels(), vec!["1", "10", "100", "1000"]);
+        let minor = scale.get_minor_breaks();
+        assert_eq!(minor.len(), 3);
+        assert!((minor[0] - 10f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_reversed_and_limited_transformed_scales() {
+        let mut reversed = ContinuousScale::new().reverse().expand(Expansion::none());
+        reversed.train(&[0.0, 10.0]);
+        assert_eq!(reversed.map(0.0), 1.0);
+        assert_eq!(reversed.map(10.0), 0.0);
+        assert_eq!(reversed.get_limits(), Some((0.0, 10.0)));
+        assert_eq!(reversed.get_breaks(), vec![0.0, 2.5, 5.0, 7.5, 10.0]);
+
+        // limits are given in data units and censor in transformed units
+        let sqrt = ContinuousScale::new()
+            .sqrt()
+            .limits(0.0, 100.0)
+            .expand(Expansion::none());
+        assert_eq!(sqrt.map(25.0), 0.5);
+        assert!(sqrt.map(400.0).is_nan());
+
+        // zero has no logarithm, so the lower limit comes from the data; the upper one
+        // still censors
+        let mut log = ContinuousScale::new()
+            .log10()
+            .limits(0.0, 100.0)
+            .expand(Expansion::none());
+        log.train(&[1.0, 1000.0]);
+        assert_eq!(log.get_limits(), Some((1.0, 100.0)));
+        assert_eq!(log.map(10.0), 0.5);
+        assert!(log.map(1000.0).is_nan());
+    }
+
+    #[test]
+    fn test_nice_domain_breaks_and_labels() {
+        let mut scale = ContinuousScale::new().nice().expand(Expansion::none());
+        scale.train(&[0.3, 9.2]);
+        assert_eq!(scale.get_limits(), Some((0.0, 10.0)));
+        assert_eq!(scale.get_breaks(), vec![0.0, 2.5, 5.0, 7.5, 10.0]);
+        assert_eq!(scale.get_labels(), vec!["0.0", "2.5", "5.0", "7.5", "10.0"]);
+        assert_eq!(scale.get_minor_breaks(), vec![1.25, 3.75, 6.25, 8.75]);
+
+        // breaks outside the limits are dropped
+        let scale = ContinuousScale::new()
+            .limits(0.5, 3.5)
+            .breaks(Breaks::Manual(vec![0.0, 1.0, 2.0, 3.0, 4.0]))
+            .labels(LabelFormat::Percent(0));
+        assert_eq!(scale.get_labels(), vec!["100%", "200%", "300%"]);
+
+        // an untrained scale still has somewhere to put things
+        assert_eq!(ContinuousScale::new().dimension(), (0.0, 1.0));
+    }
+}
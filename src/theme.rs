@@ -0,0 +1,56 @@
+// Non-data styling of a plot: backgrounds, grid lines, axis text and spacing.
+
+use crate::primitives::Color;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub background: Color,
+    pub panel_background: Color,
+    // None leaves out the grid lines
+    pub grid_color: Option<Color>,
+    pub grid_width: f64,
+    // lines at the minor breaks, None leaves them out
+    pub minor_grid_color: Option<Color>,
+    pub minor_grid_width: f64,
+    pub axis_color: Color,
+    pub text_color: Color,
+    pub font_size: f32,
+    pub title_size: f32,
+    // space around the panel in pixels: top, right, bottom, left
+    pub margin: [f64; 4],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::grey()
+    }
+}
+
+impl Theme {
+    // grey panel with white grid lines
+    pub fn grey() -> Self {
+        Theme {
+            background: Color::rgb(255, 255, 255),
+            panel_background: Color::rgb(235, 235, 235),
+            grid_color: Some(Color::rgb(255, 255, 255)),
+            grid_width: 1.0,
+            minor_grid_color: Some(Color::rgb(255, 255, 255)),
+            minor_grid_width: 0.5,
+            axis_color: Color::rgb(51, 51, 51),
+            text_color: Color::rgb(77, 77, 77),
+            font_size: 11.0,
+            title_size: 14.0,
+            margin: [20.0, 20.0, 45.0, 55.0],
+        }
+    }
+
+    // white panel with light grey grid lines
+    pub fn minimal() -> Self {
+        Theme {
+            panel_background: Color::rgb(255, 255, 255),
+            grid_color: Some(Color::rgb(235, 235, 235)),
+            minor_grid_color: Some(Color::rgb(245, 245, 245)),
+            ..Theme::grey()
+        }
+    }
+}